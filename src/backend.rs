@@ -0,0 +1,880 @@
+use std::env;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use url::Url;
+
+#[cfg(feature = "async")]
+use futures::future::{self, BoxFuture, FutureExt, TryFutureExt};
+#[cfg(feature = "async")]
+use tokio;
+
+use serde_json::Value;
+
+use errors::*;
+use format::StreamFormat;
+use info::StreamInfo;
+use {Config, Stream, StreamStatus};
+
+/// A command-line program that can check if a stream is online.
+///
+/// Implement this to check streams with a program streamlink doesn't know
+/// about, and use it with [`Backend::with_programs`].
+///
+/// [`Backend::with_programs`]: struct.Backend.html#method.with_programs
+pub trait StatusBackend: Send + Sync {
+    /// Returns a unique identifier of the backend, e.g. `"youtube-dl"`.
+    fn id(&self) -> &str;
+
+    /// Returns the programs tried by default, in order of preference.
+    fn default_programs(&self) -> Vec<String>;
+
+    /// Returns the arguments checking the stream on `url`.
+    fn args(&self, url: &Url) -> Vec<String>;
+
+    /// Interprets the exit status of the program.
+    ///
+    /// By default, exiting with 0 means online, and with 1 offline. Other
+    /// codes (or signals) mean the program never got to check.
+    fn status(&self, exit: ExitStatus) -> StreamStatus {
+        match exit.code() {
+            Some(0) => StreamStatus::Online,
+            Some(1) => StreamStatus::Offline,
+            _ => StreamStatus::Unknown,
+        }
+    }
+
+    /// Decides the status from the exit status and the error output of the
+    /// program.
+    ///
+    /// By default, well-known messages of failed checks (e.g. "is offline" or
+    /// "HTTP Error 429") take precedence over [`status`](#method.status).
+    fn classify(&self, exit: ExitStatus, stderr: &str) -> StreamStatus {
+        if exit.success() {
+            return self.status(exit);
+        }
+        classify_error(stderr).unwrap_or_else(|| self.status(exit))
+    }
+
+    /// Returns the arguments making the program connect through `proxy`.
+    ///
+    /// By default, the proxy isn't passed to the program.
+    fn proxy_args(&self, _proxy: &str) -> Vec<String> {
+        vec![]
+    }
+
+    /// Returns the arguments making the program send the cookies in the
+    /// Netscape-format file at `path`.
+    ///
+    /// By default, the cookies aren't passed to the program.
+    fn cookies_args(&self, _path: &Path) -> Vec<String> {
+        vec![]
+    }
+
+    /// Reads the formats of the stream from the output of the program run
+    /// with [`args`](#tymethod.args).
+    ///
+    /// By default, no formats are known.
+    fn formats(&self, _stdout: &str) -> Vec<StreamFormat> {
+        vec![]
+    }
+
+    /// Returns the arguments printing the details of the stream on `url`.
+    ///
+    /// By default, these are the same as [`args`](#tymethod.args), so no
+    /// details are known.
+    fn info_args(&self, url: &Url) -> Vec<String> {
+        self.args(url)
+    }
+
+    /// Reads the details of the stream with `status` from the output of the
+    /// program run with [`info_args`](#method.info_args).
+    fn info(&self, status: StreamStatus, _stdout: &str) -> StreamInfo {
+        status.into()
+    }
+
+    /// Returns the arguments checking the streams on all of `urls` with one
+    /// run of the program, or `None` (the default) if it can only check one
+    /// stream at a time.
+    ///
+    /// This is called with no URLs to find out if the program can check many
+    /// streams at once.
+    fn batch_args(&self, _urls: &[&Url]) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Reads the details of each of `streams` from the output of the program
+    /// run with [`batch_args`](#method.batch_args), in the same order.
+    ///
+    /// Streams the output tells nothing about are `None`, and get checked on
+    /// their own.
+    fn batch_info(
+        &self,
+        streams: &[&Stream],
+        _stdout: &str,
+        _stderr: &str,
+    ) -> Vec<Option<StreamInfo>> {
+        vec![None; streams.len()]
+    }
+
+    /// Returns the arguments recording the stream on `url` to `path` in
+    /// `quality`, or `None` (the default) if the program can't record.
+    fn record_args(&self, _url: &Url, _path: &Path, _quality: &str) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// Checks streams with `yt-dlp` or `youtube-dl`, listing their formats.
+pub struct YoutubeDl;
+
+impl StatusBackend for YoutubeDl {
+    fn id(&self) -> &str {
+        "youtube-dl"
+    }
+
+    fn default_programs(&self) -> Vec<String> {
+        vec!["yt-dlp".into(), "youtube-dl".into()]
+    }
+
+    fn args(&self, url: &Url) -> Vec<String> {
+        vec!["-F".into(), url.as_str().into()]
+    }
+
+    fn proxy_args(&self, proxy: &str) -> Vec<String> {
+        vec!["--proxy".into(), proxy.into()]
+    }
+
+    fn cookies_args(&self, path: &Path) -> Vec<String> {
+        vec!["--cookies".into(), path.display().to_string()]
+    }
+
+    fn record_args(&self, url: &Url, path: &Path, _quality: &str) -> Option<Vec<String>> {
+        Some(vec![
+            "-o".into(),
+            path.display().to_string(),
+            url.as_str().into(),
+        ])
+    }
+
+    fn formats(&self, stdout: &str) -> Vec<StreamFormat> {
+        StreamFormat::from_youtube_dl(stdout)
+    }
+
+    fn info_args(&self, url: &Url) -> Vec<String> {
+        vec!["-j".into(), url.as_str().into()]
+    }
+
+    fn info(&self, status: StreamStatus, stdout: &str) -> StreamInfo {
+        StreamInfo::from_youtube_dl(status, stdout)
+    }
+
+    fn batch_args(&self, urls: &[&Url]) -> Option<Vec<String>> {
+        // Keep going after a stream fails to be checked.
+        let mut args: Vec<String> = vec!["-j".into(), "--ignore-errors".into()];
+        args.extend(urls.iter().map(|url| url.as_str().into()));
+        Some(args)
+    }
+
+    /// Online streams print a line of JSON each, and the others an `ERROR:`
+    /// line (among warnings) to stderr, so both are matched to the streams
+    /// by URL or name.
+    fn batch_info(
+        &self,
+        streams: &[&Stream],
+        stdout: &str,
+        stderr: &str,
+    ) -> Vec<Option<StreamInfo>> {
+        let mut infos: Vec<Option<StreamInfo>> = vec![None; streams.len()];
+        for line in stdout.lines() {
+            let json: Value = match ::serde_json::from_str(line) {
+                Ok(json) => json,
+                Err(_) => continue,
+            };
+            let extractor = json["extractor"].as_str().unwrap_or_default();
+            let ids: Vec<&str> = ["display_id", "uploader_id", "channel_id", "id"]
+                .iter()
+                .filter_map(|field| json[field].as_str())
+                .collect();
+            let found = find_stream(streams, &infos, extractor, |stream| {
+                ["original_url", "webpage_url"]
+                    .iter()
+                    .any(|field| json[field].as_str() == Some(stream.url().as_str()))
+                    || ids.iter().any(|&id| name_matches(stream, id))
+            });
+            if let Some(index) = found {
+                infos[index] = Some(StreamInfo::from_youtube_dl(StreamStatus::Online, line));
+            }
+        }
+        for line in stderr.lines().filter(|line| line.starts_with("ERROR:")) {
+            let (extractor, id) = error_target(line).unwrap_or_default();
+            let found = find_stream(streams, &infos, extractor, |stream| {
+                line.contains(stream.url().as_str()) || name_matches(stream, id)
+            });
+            if let Some(index) = found {
+                // youtube-dl exits with 1 for each of these.
+                let status = classify_error(line).unwrap_or(StreamStatus::Offline);
+                infos[index] = Some(StreamInfo {
+                    reason: Some(line.trim().into()),
+                    ..status.into()
+                });
+            }
+        }
+        infos
+    }
+}
+
+/// Finds the first of `streams` without info yet that `matches`, preferring
+/// the ones on the platform of `extractor` (e.g. `twitch:stream`).
+fn find_stream<F>(
+    streams: &[&Stream],
+    infos: &[Option<StreamInfo>],
+    extractor: &str,
+    matches: F,
+) -> Option<usize>
+where
+    F: Fn(&Stream) -> bool,
+{
+    let platform = extractor.split(':').next().unwrap_or_default();
+    let candidates: Vec<usize> = (0..streams.len())
+        .filter(|&index| infos[index].is_none() && matches(streams[index]))
+        .collect();
+    candidates
+        .iter()
+        .cloned()
+        .find(|&index| streams[index].platform_id().eq_ignore_ascii_case(platform))
+        .or_else(|| candidates.first().cloned())
+}
+
+/// Checks if `id` (as printed by youtube-dl) is the name of `stream`.
+fn name_matches(stream: &Stream, id: &str) -> bool {
+    let id = id.trim_start_matches('@');
+    !id.is_empty()
+        && stream
+            .name()
+            .is_some_and(|name| name.eq_ignore_ascii_case(id))
+}
+
+/// Returns the extractor and the ID of what an error line of youtube-dl is
+/// about, e.g. `twitch:stream` and `gogcom` for
+/// `ERROR: [twitch:stream] gogcom: The channel is not currently live`.
+fn error_target(line: &str) -> Option<(&str, &str)> {
+    let line = line.strip_prefix("ERROR: [")?;
+    let end = line.find("] ")?;
+    let id = line[end + 2..].split(": ").next()?;
+    Some((&line[..end], id))
+}
+
+/// Checks streams with the `streamlink` CLI, listing their qualities.
+///
+/// `streamlink` fails with "No playable streams found" for offline streams.
+pub struct StreamlinkCli;
+
+impl StatusBackend for StreamlinkCli {
+    fn id(&self) -> &str {
+        "streamlink"
+    }
+
+    fn default_programs(&self) -> Vec<String> {
+        vec!["streamlink".into()]
+    }
+
+    fn args(&self, url: &Url) -> Vec<String> {
+        vec![url.as_str().into()]
+    }
+
+    fn proxy_args(&self, proxy: &str) -> Vec<String> {
+        vec!["--http-proxy".into(), proxy.into()]
+    }
+
+    fn record_args(&self, url: &Url, path: &Path, quality: &str) -> Option<Vec<String>> {
+        Some(vec![
+            "-o".into(),
+            path.display().to_string(),
+            url.as_str().into(),
+            quality.into(),
+        ])
+    }
+
+    fn formats(&self, stdout: &str) -> Vec<StreamFormat> {
+        StreamFormat::from_streamlink(stdout)
+    }
+
+    fn info_args(&self, url: &Url) -> Vec<String> {
+        vec!["--json".into(), url.as_str().into()]
+    }
+
+    fn info(&self, status: StreamStatus, stdout: &str) -> StreamInfo {
+        StreamInfo::from_streamlink(status, stdout)
+    }
+}
+
+/// The built-in status backends, as selected in [`Config::backend`].
+///
+/// [`Config::backend`]: struct.Config.html#structfield.backend
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackendKind {
+    /// [`YoutubeDl`](struct.YoutubeDl.html)
+    #[default]
+    #[serde(alias = "yt-dlp")]
+    YoutubeDl,
+    /// [`StreamlinkCli`](struct.StreamlinkCli.html)
+    Streamlink,
+}
+
+/// The program used to check stream status.
+///
+/// The first of the candidate programs that can be spawned is remembered,
+/// so `PATH` is only searched once. Clones share what they found.
+#[derive(Clone)]
+pub struct Backend {
+    backend: Arc<dyn StatusBackend>,
+    programs: Vec<String>,
+    found: Arc<Mutex<Option<String>>>,
+}
+
+impl Backend {
+    /// Creates a `youtube-dl` backend trying `programs` in order.
+    pub fn new(programs: Vec<String>) -> Self {
+        Self::with_programs(YoutubeDl, programs)
+    }
+
+    /// Creates a backend of `kind` trying its default programs.
+    pub fn from_kind(kind: BackendKind) -> Self {
+        match kind {
+            BackendKind::YoutubeDl => Self::from_backend(YoutubeDl),
+            BackendKind::Streamlink => Self::from_backend(StreamlinkCli),
+        }
+    }
+
+    /// Creates the backend set in `config`, run as its `backend_command` or
+    /// `backend_programs`, if set.
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let mut backend = Self::from_kind(config.backend);
+        match (&config.backend_command, &config.backend_programs) {
+            (Some(command), _) => backend.set_programs(vec![command.clone()]),
+            (None, Some(programs)) => backend.set_programs(programs.clone()),
+            (None, None) => {}
+        }
+        backend
+    }
+
+    /// Creates a backend running `backend` with its default programs.
+    pub fn from_backend<B: StatusBackend + 'static>(backend: B) -> Self {
+        let programs = backend.default_programs();
+        Self::with_programs(backend, programs)
+    }
+
+    /// Creates a backend running `backend` with `programs`, tried in order.
+    pub fn with_programs<B: StatusBackend + 'static>(backend: B, programs: Vec<String>) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            programs,
+            found: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Sets the programs tried, in order, to run the backend.
+    pub fn set_programs(&mut self, programs: Vec<String>) {
+        self.programs = programs;
+        self.found = Arc::new(Mutex::new(None));
+    }
+
+    /// The programs tried, in order, to run the backend.
+    pub(crate) fn programs(&self) -> &[String] {
+        &self.programs
+    }
+
+    /// The ID of the backend, e.g. `"youtube-dl"`.
+    pub(crate) fn id(&self) -> &str {
+        self.backend.id()
+    }
+
+    /// Returns the arguments making the backend send the cookies in the file
+    /// at `path`, if it can.
+    pub(crate) fn cookies_args(&self, path: &Path) -> Vec<String> {
+        self.backend.cookies_args(path)
+    }
+
+    /// Checks the stream on `url`, passing `extra_args` before the backend's
+    /// own arguments, and killing the backend after `timeout`.
+    pub(crate) fn check(
+        &self,
+        url: &Url,
+        extra_args: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<StreamStatus> {
+        self.check_detailed(url, extra_args, timeout)
+            .map(|(status, _)| status)
+    }
+
+    /// Like [`check`](#method.check), but also returns why the check failed,
+    /// as told by the backend.
+    pub(crate) fn check_detailed(
+        &self,
+        url: &Url,
+        extra_args: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<(StreamStatus, Option<String>)> {
+        let output = self.run(
+            url.as_str(),
+            extra_args,
+            self.backend.args(url),
+            timeout,
+            false,
+        )?;
+        Ok((
+            self.backend.classify(output.exit, &output.stderr),
+            output.reason(),
+        ))
+    }
+
+    /// Like [`check`](#method.check), but also reads the formats of the
+    /// stream from the output of the backend.
+    pub(crate) fn formats(
+        &self,
+        url: &Url,
+        extra_args: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<(StreamStatus, Vec<StreamFormat>)> {
+        let output = self.run(
+            url.as_str(),
+            extra_args,
+            self.backend.args(url),
+            timeout,
+            true,
+        )?;
+        Ok((
+            self.backend.classify(output.exit, &output.stderr),
+            self.backend.formats(&output.stdout),
+        ))
+    }
+
+    /// Like [`check`](#method.check), but reads the details of the stream
+    /// from the output of the backend.
+    pub(crate) fn info(
+        &self,
+        url: &Url,
+        extra_args: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<StreamInfo> {
+        let args = self.backend.info_args(url);
+        let output = self.run(url.as_str(), extra_args, args, timeout, true)?;
+        let status = self.backend.classify(output.exit, &output.stderr);
+        let mut info = self.backend.info(status, &output.stdout);
+        info.reason = output.reason();
+        Ok(info)
+    }
+
+    /// Like [`check`](#method.check), but without blocking the thread while
+    /// the backend runs. Must be polled within a tokio runtime.
+    #[cfg(feature = "async")]
+    pub(crate) fn check_async(
+        &self,
+        url: &Url,
+        extra_args: &[String],
+        timeout: Option<Duration>,
+    ) -> BoxFuture<'static, Result<StreamStatus>> {
+        let backend = self.clone();
+        let mut args = extra_args.to_vec();
+        args.extend(self.backend.args(url));
+        let url = url.to_string();
+        // Spawn only once polled, as tokio needs its runtime for that.
+        future::lazy(move |_| {
+            let child = backend.find(|program| spawn_async(program, &args))?;
+            Ok((backend, child))
+        })
+        .and_then(move |(backend, child)| {
+            // Dropping the child on timeout kills it.
+            let output = child.wait_with_output().map_err(Error::from);
+            let output = match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, output)
+                    .map(move |output| {
+                        output.unwrap_or_else(|_| Err(ErrorKind::CheckTimeout(url, timeout).into()))
+                    })
+                    .boxed(),
+                None => output.boxed(),
+            };
+            output.map_ok(move |output| {
+                let stderr = tail(&output.stderr);
+                backend.backend.classify(output.status, &stderr)
+            })
+        })
+        .boxed()
+    }
+
+    /// Checks all of `streams` with one run of the backend, if it can, and
+    /// with a timeout of `timeout` for each of them.
+    ///
+    /// Streams the backend told nothing about are `None`.
+    pub(crate) fn info_batch(
+        &self,
+        streams: &[&Stream],
+        extra_args: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Option<StreamInfo>>> {
+        let urls: Vec<&Url> = streams.iter().map(|stream| stream.url()).collect();
+        let args = match self.backend.batch_args(&urls) {
+            Some(args) => args,
+            None => return Ok(vec![None; streams.len()]),
+        };
+        let target = urls
+            .iter()
+            .map(|url| url.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let timeout = timeout.map(|timeout| timeout * streams.len() as u32);
+        let output = self.run(&target, extra_args, args, timeout, true)?;
+        Ok(self
+            .backend
+            .batch_info(streams, &output.stdout, &output.stderr))
+    }
+
+    /// Checks if the backend can check many streams with one run.
+    pub(crate) fn can_batch(&self) -> bool {
+        self.backend.batch_args(&[]).is_some()
+    }
+
+    /// Runs the backend with `extra_args` followed by `args` to check the
+    /// stream on `target`, capturing its output if `capture` is set.
+    ///
+    /// Only the end of the error output is kept.
+    fn run(
+        &self,
+        target: &str,
+        extra_args: &[String],
+        args: Vec<String>,
+        timeout: Option<Duration>,
+        capture: bool,
+    ) -> Result<Output> {
+        let mut all_args = extra_args.to_vec();
+        all_args.extend(args);
+        let mut child = self.spawn(&all_args, capture)?;
+        // Read the output while waiting, so the backend doesn't block on a
+        // full pipe.
+        let stdout = child.stdout.take().map(|mut stdout| {
+            thread::spawn(move || {
+                let mut output = String::new();
+                let _ = stdout.read_to_string(&mut output);
+                output
+            })
+        });
+        let stderr = child
+            .stderr
+            .take()
+            .map(|stderr| thread::spawn(move || read_tail(stderr)));
+        let exit = match timeout {
+            Some(timeout) => match wait_timeout(child, timeout)? {
+                Some(exit) => exit,
+                None => bail!(ErrorKind::CheckTimeout(target.into(), timeout)),
+            },
+            None => child.wait()?,
+        };
+        let stdout = stdout
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        let stderr = stderr
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+        Ok(Output {
+            exit,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Starts the backend with `args`, piping its output if `capture` is set
+    /// and discarding it otherwise. The error output is always piped.
+    fn spawn(&self, args: &[String], capture: bool) -> Result<Child> {
+        self.find(|program| spawn(program, args, capture))
+    }
+
+    /// Records the stream on `url` to `path` in `quality`, passing
+    /// `extra_args` before the backend's own arguments. The backend is given
+    /// the terminal, so its progress is shown as is.
+    pub(crate) fn record(
+        &self,
+        url: &Url,
+        path: &Path,
+        quality: &str,
+        extra_args: &[String],
+    ) -> Result<Child> {
+        let args = self
+            .backend
+            .record_args(url, path, quality)
+            .ok_or_else(|| ErrorKind::RecordUnsupported(self.backend.id().into()))?;
+        let mut all_args = extra_args.to_vec();
+        all_args.extend(args);
+        self.find(|program| start(program, &all_args))
+    }
+
+    /// Starts the first of the programs that exists with `spawn`.
+    fn find<C, S>(&self, spawn: S) -> Result<C>
+    where
+        S: Fn(&str) -> Result<Option<C>>,
+    {
+        let mut found = self.found.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(ref program) = *found {
+            return spawn(program)?
+                .ok_or_else(|| ErrorKind::BackendMissing(program.clone()).into());
+        }
+        for program in &self.programs {
+            if let Some(child) = spawn(program)? {
+                *found = Some(program.clone());
+                return Ok(child);
+            }
+        }
+        bail!(ErrorKind::BackendMissing(self.programs.join(", ")))
+    }
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Self::from_kind(BackendKind::default())
+    }
+}
+
+impl fmt::Debug for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Backend")
+            .field("backend", &self.backend.id())
+            .field("programs", &self.programs)
+            .field("found", &self.found)
+            .finish()
+    }
+}
+
+/// Options for checking stream status.
+///
+/// # Examples
+///
+/// ```rust
+/// use streamlink::{Backend, CheckOptions};
+///
+/// let options = CheckOptions {
+///     backend: Backend::new(vec!["youtube-dl".into()]),
+///     args: vec!["--geo-bypass".into()],
+///     cookies_file: Some("cookies.txt".into()),
+///     ..CheckOptions::default()
+/// };
+/// ```
+#[derive(Debug)]
+pub struct CheckOptions {
+    /// The program to run.
+    pub backend: Backend,
+    /// Extra arguments passed to the backend, each as a separate argument.
+    pub args: Vec<String>,
+    /// How long the backend may take before it's killed. Defaults to
+    /// [`DEFAULT_TIMEOUT`], and `None` waits forever.
+    ///
+    /// [`DEFAULT_TIMEOUT`]: constant.DEFAULT_TIMEOUT.html
+    pub timeout: Option<Duration>,
+    /// The proxy the backend connects through, e.g. `"http://proxy:3128"`.
+    /// Defaults to the one set in the environment, see [`env_proxy`].
+    ///
+    /// [`env_proxy`]: fn.env_proxy.html
+    pub proxy: Option<String>,
+    /// A Netscape-format cookies file sent along with the checks, e.g. to
+    /// check age-restricted streams.
+    pub cookies_file: Option<PathBuf>,
+}
+
+impl CheckOptions {
+    /// Returns the arguments passed to the backend before its own: the ones
+    /// setting the proxy and the cookies, followed by
+    /// [`args`](#structfield.args).
+    pub(crate) fn backend_args(&self) -> Vec<String> {
+        let backend = &self.backend.backend;
+        let mut args = match self.proxy {
+            Some(ref proxy) => backend.proxy_args(proxy),
+            None => vec![],
+        };
+        if let Some(ref path) = self.cookies_file {
+            args.extend(backend.cookies_args(path));
+        }
+        args.extend(self.args.iter().cloned());
+        args
+    }
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            backend: Backend::default(),
+            args: vec![],
+            timeout: Some(DEFAULT_TIMEOUT),
+            proxy: env_proxy(),
+            cookies_file: None,
+        }
+    }
+}
+
+/// Returns the proxy set in the `HTTPS_PROXY` or `HTTP_PROXY` environment
+/// variable (or their lowercase variants), in that order.
+pub fn env_proxy() -> Option<String> {
+    proxy_from(|name| env::var(name).ok())
+}
+
+/// Like [`env_proxy`], but reads the variables with `var`.
+pub(crate) fn proxy_from<F>(var: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|proxy| !proxy.is_empty())
+}
+
+/// What a backend left behind after checking a stream.
+struct Output {
+    exit: ExitStatus,
+    stdout: String,
+    stderr: String,
+}
+
+impl Output {
+    /// Returns the error output of a failed check.
+    fn reason(&self) -> Option<String> {
+        Some(self.stderr.trim())
+            .filter(|stderr| !self.exit.success() && !stderr.is_empty())
+            .map(String::from)
+    }
+}
+
+/// Well-known messages of failed checks, and the status they tell.
+///
+/// Messages telling the stream doesn't exist (anymore) count as offline,
+/// while the ones about streamlink being refused or broken mean the status
+/// is unknown.
+const ERROR_MESSAGES: &[(&str, StreamStatus)] = &[
+    ("is offline", StreamStatus::Offline),
+    ("is not currently live", StreamStatus::Offline),
+    ("No playable streams found", StreamStatus::Offline),
+    ("This video is unavailable", StreamStatus::Offline),
+    ("This live event will begin", StreamStatus::Offline),
+    ("HTTP Error 429", StreamStatus::Unknown),
+    ("Too Many Requests", StreamStatus::Unknown),
+    ("HTTP Error 403", StreamStatus::Unknown),
+    ("Sign in to confirm", StreamStatus::Unknown),
+    ("Unsupported URL", StreamStatus::Unknown),
+];
+
+/// Looks for a well-known message in the error output of a failed check.
+fn classify_error(stderr: &str) -> Option<StreamStatus> {
+    ERROR_MESSAGES
+        .iter()
+        .find(|(message, _)| stderr.contains(message))
+        .map(|&(_, status)| status)
+}
+
+/// How much of the error output of a backend is kept.
+const STDERR_LIMIT: usize = 8 * 1024;
+
+/// Reads `reader` to the end, keeping the last [`STDERR_LIMIT`] bytes.
+fn read_tail<R: Read>(mut reader: R) -> String {
+    let mut tail = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                tail.extend_from_slice(&buf[..n]);
+                if tail.len() > STDERR_LIMIT {
+                    let excess = tail.len() - STDERR_LIMIT;
+                    tail.drain(..excess);
+                }
+            }
+        }
+    }
+    String::from_utf8_lossy(&tail).into_owned()
+}
+
+/// Returns the last [`STDERR_LIMIT`] bytes of `output`.
+#[cfg(feature = "async")]
+fn tail(output: &[u8]) -> String {
+    let start = output.len().saturating_sub(STDERR_LIMIT);
+    String::from_utf8_lossy(&output[start..]).into_owned()
+}
+
+/// How long a backend may take to check a stream by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often to check if a backend has finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Waits for `child` to exit, killing it after `timeout`.
+///
+/// Returns `None` if it was killed.
+fn wait_timeout(mut child: Child, timeout: Duration) -> Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // The backend may have exited in the meantime, so don't fail if
+            // there's nothing left to kill.
+            let _ = child.kill();
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Starts `program`, returning `None` if it doesn't exist.
+fn spawn(program: &str, args: &[String], capture: bool) -> Result<Option<Child>> {
+    match Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(if capture {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => Ok(Some(child)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Like [`spawn`], but leaves the output of `program` to the terminal.
+fn start(program: &str, args: &[String]) -> Result<Option<Child>> {
+    match Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => Ok(Some(child)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Like [`spawn`], but for tokio, discarding the output of the program
+/// except for its errors.
+#[cfg(feature = "async")]
+fn spawn_async(program: &str, args: &[String]) -> Result<Option<tokio::process::Child>> {
+    match tokio::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => Ok(Some(child)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
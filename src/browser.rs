@@ -0,0 +1,45 @@
+use std::env;
+use std::process::Command;
+
+/// How to open a stream in the browser with
+/// [`Stream::open_with`](struct.Stream.html#method.open_with).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opener {
+    /// The program opening URLs, e.g. `"xdg-open"`.
+    pub program: String,
+    /// Extra arguments passed to the program, before the URL.
+    pub args: Vec<String>,
+}
+
+impl Opener {
+    /// The opener of `os`, as told by `std::env::consts::OS`: `open` on
+    /// macOS, the URL handler of the shell on Windows, and `xdg-open`
+    /// elsewhere.
+    pub fn for_os(os: &str) -> Self {
+        let (program, args): (&str, &[&str]) = match os {
+            "macos" => ("open", &[]),
+            // `start` would need the URL quoted for `cmd`, which `&` in
+            // query strings makes tricky.
+            "windows" => ("rundll32", &["url.dll,FileProtocolHandler"]),
+            _ => ("xdg-open", &[]),
+        };
+        Opener {
+            program: program.into(),
+            args: args.iter().map(|&arg| arg.into()).collect(),
+        }
+    }
+
+    /// Returns the command opening `url`.
+    pub(crate) fn command(&self, url: &str) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).arg(url);
+        command
+    }
+}
+
+impl Default for Opener {
+    /// The opener of the OS this runs on, see [`for_os`](#method.for_os).
+    fn default() -> Self {
+        Self::for_os(env::consts::OS)
+    }
+}
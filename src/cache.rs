@@ -0,0 +1,132 @@
+use dirs;
+use serde_json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use errors::*;
+use {Stream, StreamStatus};
+
+/// A status as stored in the cache.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub status: StreamStatus,
+    /// When the status was checked, in seconds since the Unix epoch.
+    pub checked_at: u64,
+}
+
+impl CacheEntry {
+    /// Returns when the status was checked.
+    pub fn time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.checked_at)
+    }
+
+    /// Returns how long ago the status was checked.
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.time())
+            .unwrap_or_default()
+    }
+}
+
+/// Statuses checked earlier, kept in a JSON file by canonical stream URL.
+///
+/// Only streams found to be online or offline are cached. The file is
+/// replaced as a whole on every save, so other processes reading it never
+/// see it half-written.
+#[derive(Debug)]
+pub struct StatusCache {
+    path: PathBuf,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl StatusCache {
+    /// Opens the cache in `path`, whose statuses are used for `ttl`.
+    ///
+    /// A missing or unreadable file makes for an empty cache.
+    pub fn open<P: AsRef<Path>>(path: P, ttl: Duration) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = load(&path);
+        Self {
+            path,
+            ttl,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Returns the default location of the cache, e.g.
+    /// `~/.cache/streamlink-rs/status.json` on Linux.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("streamlink-rs/status.json"))
+    }
+
+    /// Returns the cached status of `stream`, unless it's older than the TTL.
+    pub fn get(&self, stream: &Stream) -> Option<CacheEntry> {
+        self.lock()
+            .get(stream.canonical_url().as_str())
+            .cloned()
+            .filter(|entry| entry.age() < self.ttl)
+    }
+
+    /// Remembers that `stream` was just found to be `status`.
+    pub fn insert(&self, stream: &Stream, status: StreamStatus) {
+        if status == StreamStatus::Unknown {
+            return;
+        }
+        let checked_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.lock().insert(
+            stream.canonical_url().as_str().into(),
+            CacheEntry { status, checked_at },
+        );
+    }
+
+    /// Writes the cache to its file, keeping the newer entries other
+    /// processes wrote in the meantime.
+    pub fn save(&self) -> Result<()> {
+        let mut entries = self.lock();
+        for (url, entry) in load(&self.path) {
+            let newer = entries
+                .get(&url)
+                .is_none_or(|ours| ours.checked_at < entry.checked_at);
+            if newer {
+                entries.insert(url, entry);
+            }
+        }
+        let json = serde_json::to_string(&*entries).chain_err(|| "unable to serialize cache")?;
+        write_atomic(&self.path, &json)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, CacheEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Replaces `path` with `contents`, creating its directory if needed.
+///
+/// Other processes see either the old or the new contents, never a mix.
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Renaming is atomic, so write to a file of our own first.
+    let mut tmp = path.to_path_buf().into_os_string();
+    tmp.push(format!(".{}.tmp", process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reads the entries in `path`, if any.
+fn load(path: &Path) -> HashMap<String, CacheEntry> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
@@ -1,24 +1,1741 @@
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::Path;
+use dirs;
+use serde::de::DeserializeOwned;
+use serde_json::{self, Map, Value};
+#[cfg(feature = "yaml")]
+use serde_yaml;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use toml;
 
+use backend::{Backend, BackendKind, DEFAULT_TIMEOUT};
+use cache::write_atomic;
 use errors::*;
+use platform::{is_known_platform, PlatformRegistry};
+use provider::DEFAULT_BATCH_SIZE;
+use url::Url;
+use {PlatformKind, Stream, StreamStatus};
 
-#[derive(Serialize, Deserialize, Debug)]
+/// What `streamlink-rs` checks, and how.
+///
+/// Configs are read from TOML, JSON, or, with the `yaml` feature, YAML files,
+/// as told by their extension.
+///
+/// The stream URLs, proxy, cookies file, API credentials and webhook URL may
+/// refer to environment variables as `${NAME}`, e.g. to keep secrets out of the
+/// config, with `$$` standing for a `$`. Stream names are taken as written.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Config {
+    /// The version of the config, [`CONFIG_VERSION`] for configs written by
+    /// this version of streamlink-rs. Configs without one are version 0, and
+    /// older configs are migrated as they're read.
+    ///
+    /// [`CONFIG_VERSION`]: constant.CONFIG_VERSION.html
+    #[serde(default)]
+    pub version: u32,
+    /// Other configs to read before this one, e.g. `["work.toml"]`, relative
+    /// to the directory of this one. A leading `~` stands for the home
+    /// directory.
+    ///
+    /// Their streams are checked before the ones of this config, and the
+    /// rest of their settings are overridden by the ones of this config, or
+    /// of the configs included after them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    /// URLs of the streams to check.
+    #[serde(default)]
     pub stream_urls: Vec<String>,
+    /// Streams to check, along with how to call them, as `[[streams]]`
+    /// tables. Checked after the ones in [`stream_urls`].
+    ///
+    /// [`stream_urls`]: #structfield.stream_urls
+    #[serde(default)]
+    pub streams: Vec<StreamConfig>,
+    /// Extra hosts to accept as streams, keyed by host (or host suffix).
+    #[serde(default)]
+    pub platforms: HashMap<String, PlatformConfig>,
+    /// Settings for the streams on each platform, as `[platform.<id>]`
+    /// tables keyed by platform, e.g. `[platform.youtube]`.
+    #[serde(default)]
+    pub platform: HashMap<String, PlatformSettings>,
+    /// Accept URLs on any host, leaving it to the backend to check them.
+    #[serde(default)]
+    pub allow_other: bool,
+    /// The backend checking stream status.
+    #[serde(default)]
+    pub backend: BackendKind,
+    /// Programs tried, in order, to run the backend. Defaults to the
+    /// backend's own, e.g. `yt-dlp`, then `youtube-dl`.
+    pub backend_programs: Option<Vec<String>>,
+    /// A program to run the backend with instead of [`backend_programs`].
+    ///
+    /// [`backend_programs`]: #structfield.backend_programs
+    pub backend_command: Option<String>,
+    /// Extra arguments passed to the backend, e.g. `["--geo-bypass"]`.
+    ///
+    /// They're passed after the ones of [`cookies_file`], so both are used,
+    /// and a `--cookies` argument here takes precedence.
+    ///
+    /// [`cookies_file`]: #structfield.cookies_file
+    #[serde(default)]
+    pub backend_args: Vec<String>,
+    /// A Netscape-format cookies file passed to the backend (as `--cookies`
+    /// with `youtube-dl`), e.g. to check age-restricted streams. A leading
+    /// `~` stands for the home directory. The `streamlink` backend can't be
+    /// passed one, as told by [`warnings`](#method.warnings).
+    pub cookies_file: Option<PathBuf>,
+    /// How many streams to check with one run of the backend, if it can
+    /// check many at once (like `youtube-dl`). 1 checks them one by one.
+    #[serde(default = "default_backend_batch_size")]
+    pub backend_batch_size: usize,
+    /// How many seconds a backend may take to check a stream, or 0 to wait
+    /// forever.
+    #[serde(default = "default_check_timeout_secs")]
+    pub check_timeout_secs: u64,
+    /// The proxy to check streams through, e.g. `"http://proxy:3128"`.
+    /// Defaults to the `HTTPS_PROXY` or `HTTP_PROXY` environment variable.
+    pub proxy: Option<String>,
+    /// How many times to retry a status check that failed.
+    #[serde(default)]
+    pub retries: u32,
+    /// How many milliseconds to wait before the first retry. Each further
+    /// retry waits twice as long as the previous one.
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// How many milliseconds to wait between checks against the same
+    /// platform, e.g. to stay within the rate limits of Twitch.
+    #[serde(default)]
+    pub min_interval_ms: u64,
+    /// How many seconds to reuse checked statuses for, or 0 (the default) to
+    /// always check. See [`StatusCache`](struct.StatusCache.html).
+    #[serde(default)]
+    pub cache_ttl_secs: u64,
+    /// How many seconds `strs --watch` waits between checking the streams.
+    /// Can't be 0.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// How many streams to check at once. 1 checks them one by one.
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// When to color the output.
+    #[serde(default)]
+    pub color: ColorChoice,
+    /// The order to print the statuses in.
+    #[serde(default)]
+    pub sort: SortOrder,
+    /// What to group the statuses under headers by, if anything.
+    #[serde(default)]
+    pub group_by: GroupBy,
+    /// The [`Template`](struct.Template.html) to print each status as, e.g.
+    /// `"{name}:{status}"`, instead of the
+    /// [`DEFAULT_TEMPLATE`](constant.DEFAULT_TEMPLATE.html).
+    pub format: Option<String>,
+    /// The player to watch streams with, e.g. `"mpv"` or `"vlc"`.
+    #[serde(default = "default_player")]
+    pub player: String,
+    /// Extra arguments passed to the player, e.g. `["--no-border"]`.
+    #[serde(default)]
+    pub player_args: Vec<String>,
+    /// The quality to watch streams in, e.g. `"best"` or `"720p"`.
+    #[serde(default = "default_quality")]
+    pub quality: String,
+    /// The directory `strs record` records streams into, instead of the
+    /// current one. A leading `~` stands for the home directory.
+    pub record_dir: Option<PathBuf>,
+    /// What `strs record` names recordings, e.g. `"{name}-{date}"` (the
+    /// default), see
+    /// [`RecordOptions::file_name`](struct.RecordOptions.html#structfield.file_name).
+    pub record_name: Option<String>,
+    /// Whether to show a desktop notification when a stream goes online,
+    /// with the `notifications` feature.
+    #[serde(default)]
+    pub notify: bool,
+    /// Whether to also notify of streams going offline.
+    #[serde(default)]
+    pub notify_offline: bool,
+    /// Where to post streams going online or offline to, with the
+    /// `webhooks` feature.
+    pub webhook: Option<WebhookConfig>,
+    /// Twitch API credentials, used with the `twitch-api` feature.
+    pub twitch: Option<TwitchConfig>,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            include: vec![],
+            stream_urls: vec![],
+            streams: vec![],
+            platforms: HashMap::new(),
+            platform: HashMap::new(),
+            allow_other: false,
+            backend: BackendKind::default(),
+            backend_programs: None,
+            backend_command: None,
+            backend_args: vec![],
+            cookies_file: None,
+            backend_batch_size: default_backend_batch_size(),
+            check_timeout_secs: default_check_timeout_secs(),
+            proxy: None,
+            retries: 0,
+            retry_delay_ms: default_retry_delay_ms(),
+            min_interval_ms: 0,
+            cache_ttl_secs: 0,
+            poll_interval_secs: default_poll_interval_secs(),
+            concurrency: default_concurrency(),
+            color: ColorChoice::default(),
+            sort: SortOrder::default(),
+            group_by: GroupBy::default(),
+            format: None,
+            player: default_player(),
+            player_args: vec![],
+            quality: default_quality(),
+            record_dir: None,
+            record_name: None,
+            notify: false,
+            notify_offline: false,
+            webhook: None,
+            twitch: None,
+        }
+    }
+}
+
+/// A stream to check, as a `[[streams]]` table.
+///
+/// ```toml
+/// [[streams]]
+/// url = "https://twitch.tv/northernlion"
+/// name = "Northernlion"
+/// tags = ["speedrun", "variety"]
+/// # Watched in 720p instead of the default quality.
+/// quality = "720p"
+/// # Checked for up to a minute.
+/// check_timeout_secs = 60
+/// # Not checked for now.
+/// enabled = false
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StreamConfig {
+    pub url: String,
+    /// What to call the stream instead of the name in its URL.
+    pub name: Option<String>,
+    /// Groups the stream is in, to check only some of the streams.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Whether to check the stream. Disabled streams are still listed.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// The quality to watch the stream in, instead of
+    /// [`Config::quality`](struct.Config.html#structfield.quality).
+    pub quality: Option<String>,
+    /// The player to watch the stream with, instead of
+    /// [`Config::player`](struct.Config.html#structfield.player).
+    pub player: Option<String>,
+    /// How many seconds a backend may take to check the stream, instead of
+    /// the ones set for its platform, or
+    /// [`Config::check_timeout_secs`](struct.Config.html#structfield.check_timeout_secs).
+    pub check_timeout_secs: Option<u64>,
+    /// Extra arguments passed to the backend to check the stream, after the
+    /// ones set for its platform.
+    #[serde(default)]
+    pub backend_args: Vec<String>,
+    /// A cookies file passed to the backend to check the stream, instead of
+    /// [`Config::cookies_file`](struct.Config.html#structfield.cookies_file).
+    /// A leading `~` stands for the home directory.
+    pub cookies_file: Option<PathBuf>,
+    /// Whether to notify of the stream going online, instead of
+    /// [`Config::notify`](struct.Config.html#structfield.notify).
+    pub notify: Option<bool>,
+}
+
+/// Settings for the streams on one platform, as a `[platform.<id>]` table,
+/// overriding the global ones. Settings of a stream in
+/// [`Config::streams`](struct.Config.html#structfield.streams) override
+/// these in turn.
+///
+/// ```toml
+/// [platform.youtube]
+/// check_timeout_secs = 60
+/// api_key = "..."
+///
+/// [platform.kick]
+/// backend_args = ["--impersonate", "chrome"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformSettings {
+    /// How many seconds a backend may take to check a stream, instead of
+    /// [`Config::check_timeout_secs`](struct.Config.html#structfield.check_timeout_secs).
+    pub check_timeout_secs: Option<u64>,
+    /// Extra arguments passed to the backend, after
+    /// [`Config::backend_args`](struct.Config.html#structfield.backend_args).
+    #[serde(default)]
+    pub backend_args: Vec<String>,
+    /// A key for the API of the platform, used with its feature. Only the
+    /// YouTube Data API takes one, with the `youtube-api` feature.
+    pub api_key: Option<String>,
+}
+
+/// Something odd about a config, which is still used as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigWarning {
+    /// The stream on (canonical) `url` is configured more than once, in the
+    /// `first` and `duplicate` entries of [`stream_urls`] and [`streams`],
+    /// counted together from 1. It's checked only once.
+    ///
+    /// [`stream_urls`]: struct.Config.html#structfield.stream_urls
+    /// [`streams`]: struct.Config.html#structfield.streams
+    DuplicateStream {
+        url: String,
+        first: usize,
+        duplicate: usize,
+    },
+    /// There are [`platform`] settings for a platform streamlink-rs doesn't
+    /// know. They're not used.
+    ///
+    /// [`platform`]: struct.Config.html#structfield.platform
+    UnknownPlatform(String),
+    /// Notifications are turned on, but streamlink-rs was built without the
+    /// `notifications` feature, so none are shown.
+    NotificationsUnavailable,
+    /// A webhook is set, but streamlink-rs was built without the `webhooks`
+    /// feature, so nothing is posted to it.
+    WebhooksUnavailable,
+    /// A cookies file is set, but the backend, e.g. `streamlink`, can't be
+    /// passed one, so no cookies are sent.
+    CookiesUnsupported(String),
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigWarning::DuplicateStream {
+                ref url,
+                first,
+                duplicate,
+            } => write!(
+                f,
+                "duplicate stream {} (entries {} and {})",
+                url, first, duplicate
+            ),
+            ConfigWarning::UnknownPlatform(ref platform) => {
+                write!(f, "settings for unknown platform '{}'", platform)
+            }
+            ConfigWarning::NotificationsUnavailable => write!(
+                f,
+                "notifications are on, but streamlink-rs was built without them"
+            ),
+            ConfigWarning::WebhooksUnavailable => write!(
+                f,
+                "a webhook is set, but streamlink-rs was built without webhooks"
+            ),
+            ConfigWarning::CookiesUnsupported(ref backend) => write!(
+                f,
+                "a cookies file is set, but the {} backend can't send cookies",
+                backend
+            ),
+        }
+    }
+}
+
+/// What a config was read from, as listed by `strs config show`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// A config given on the command line, or found by default.
+    File(PathBuf),
+    /// A config included by the config in `by`.
+    Include { path: PathBuf, by: PathBuf },
+    /// An environment variable naming the config, or referred to in it.
+    Variable(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigSource::File(ref path) => write!(f, "file {}", path.display()),
+            ConfigSource::Include { ref path, ref by } => {
+                write!(f, "file {} (included by {})", path.display(), by.display())
+            }
+            ConfigSource::Variable(ref name) => write!(f, "env {}", name),
+        }
+    }
+}
+
+/// What [`Config::import_streams`] did with the streams it was given.
+///
+/// [`Config::import_streams`]: struct.Config.html#method.import_streams
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// The URLs of the streams added.
+    pub added: Vec<String>,
+    /// The URLs of the streams skipped, as they were already in the config.
+    pub skipped: Vec<String>,
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let streams = |count: usize| if count == 1 { "stream" } else { "streams" };
+        write!(
+            f,
+            "added {} {}, skipped {} already in the config",
+            self.added.len(),
+            streams(self.added.len()),
+            self.skipped.len()
+        )
+    }
+}
+
+/// When to color the output, as set in [`Config::color`].
+///
+/// [`Config::color`]: struct.Config.html#structfield.color
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Only when stdout is a terminal, and the `NO_COLOR` environment
+    /// variable isn't set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Tells whether to color the output.
+    pub fn enabled(self) -> bool {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.enabled_with(io::stdout().is_terminal(), no_color)
+    }
+
+    /// Like [`enabled`](#method.enabled), but as if stdout `is_terminal`,
+    /// and `NO_COLOR` set if `no_color`.
+    pub(crate) fn enabled_with(self, is_terminal: bool, no_color: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal && !no_color,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// The order to print the statuses in, as set in [`Config::sort`].
+///
+/// Streams that compare equal stay in the order of the config.
+///
+/// [`Config::sort`]: struct.Config.html#structfield.sort
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// The order of the streams in the config.
+    #[default]
+    Config,
+    /// By the names the streams are shown as, ignoring case.
+    Name,
+    /// The online streams first, then the offline ones, then the ones that
+    /// couldn't be checked.
+    Status,
+    /// By the IDs of the platforms the streams are on.
+    Platform,
+}
+
+/// What to group the statuses under headers by, as set in
+/// [`Config::group_by`].
+///
+/// The statuses in each group stay in [`SortOrder`](enum.SortOrder.html).
+///
+/// [`Config::group_by`]: struct.Config.html#structfield.group_by
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Nothing, the statuses aren't grouped.
+    #[default]
+    None,
+    /// The IDs of the platforms the streams are on.
+    Platform,
+}
+
+/// A webhook to post streams going online or offline to, e.g. of Discord or
+/// Slack, as set in [`Config::webhook`].
+///
+/// [`Config::webhook`]: struct.Config.html#structfield.webhook
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    /// The URL to post to.
+    pub url: String,
+    /// The JSON to post, as a [`Template`](struct.Template.html), e.g.
+    /// `'{{"content": "{name} is {status}"}}'`. What's filled in is escaped
+    /// to fit in JSON strings. Defaults to the
+    /// [`StatusReport`](struct.StatusReport.html) as JSON.
+    pub body: Option<String>,
+    /// The statuses to post streams going to.
+    #[serde(default = "default_webhook_on")]
+    pub on: Vec<StreamStatus>,
+}
+
+/// Credentials of a Twitch app, registered at <https://dev.twitch.tv/console>.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TwitchConfig {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+/// Describes how to handle streams on a host unknown to streamlink.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlatformConfig {
+    /// Index of the path segment holding the stream name.
+    /// If not set, the whole path is used.
+    pub name_segment: Option<usize>,
+}
+
+impl StreamConfig {
+    /// A stream at `url`, with nothing else set.
+    pub fn new(url: String) -> Self {
+        StreamConfig {
+            url,
+            name: None,
+            tags: vec![],
+            enabled: true,
+            quality: None,
+            player: None,
+            check_timeout_secs: None,
+            backend_args: vec![],
+            cookies_file: None,
+            notify: None,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_player() -> String {
+    "mpv".into()
+}
+
+fn default_quality() -> String {
+    "best".into()
+}
+
+fn default_backend_batch_size() -> usize {
+    DEFAULT_BATCH_SIZE
+}
+
+fn default_check_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT.as_secs()
+}
+
+fn default_poll_interval_secs() -> u64 {
+    300
+}
+
+fn default_retry_delay_ms() -> u64 {
+    500
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+fn default_webhook_on() -> Vec<StreamStatus> {
+    vec![StreamStatus::Online]
+}
+
+/// The formats configs can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Toml,
+    Json,
+    #[cfg(feature = "yaml")]
+    Yaml,
+}
+
+impl Format {
+    /// Tells the format of the config in `path` by its extension. Files
+    /// without one are TOML.
+    fn from_path(path: &Path) -> Result<Self> {
+        let extension = match path.extension() {
+            Some(extension) => extension.to_string_lossy().to_lowercase(),
+            None => return Ok(Format::Toml),
+        };
+        match extension.as_str() {
+            "toml" => Ok(Format::Toml),
+            "json" => Ok(Format::Json),
+            #[cfg(feature = "yaml")]
+            "yaml" | "yml" => Ok(Format::Yaml),
+            _ => bail!(ErrorKind::UnsupportedConfigFormat(extension)),
+        }
+    }
+
+    fn write(self, config: &Config) -> Result<String> {
+        let contents = match self {
+            // Values have to come before tables in TOML, which `toml::Value`
+            // takes care of.
+            Format::Toml => toml::Value::try_from(config)
+                .and_then(|value| toml::to_string_pretty(&value))
+                .map_err(|e| e.to_string()),
+            Format::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(config).map_err(|e| e.to_string()),
+        };
+        contents.map_err(|e| format!("unable to serialize config: {}", e).into())
+    }
+
+    fn parse<T: DeserializeOwned>(self, contents: &str) -> ::std::result::Result<T, ParseError> {
+        match self {
+            Format::Toml => toml::from_str(contents).map_err(|e| {
+                let position = e.line_col().map(|(line, column)| (line + 1, column + 1));
+                ParseError::new(e, position)
+            }),
+            Format::Json => serde_json::from_str(contents).map_err(|e| {
+                let position = Some((e.line(), e.column())).filter(|&(line, _)| line > 0);
+                ParseError::new(e, position)
+            }),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::from_str(contents).map_err(|e| {
+                let position = e.location().map(|l| (l.line(), l.column()));
+                ParseError::new(e, position)
+            }),
+        }
+    }
+}
+
+/// Why a config couldn't be parsed, and where, as 1-based line and column.
+struct ParseError {
+    message: String,
+    position: Option<(usize, usize)>,
+}
+
+impl ParseError {
+    fn new<E: fmt::Display>(error: E, position: Option<(usize, usize)>) -> Self {
+        let mut message = error.to_string();
+        // The parsers tell the position at the end of their message, which
+        // is told apart instead.
+        if position.is_some() {
+            if let Some(at) = message.rfind(" at line ") {
+                message.truncate(at);
+            }
+        }
+        Self { message, position }
+    }
+}
+
+/// The version of configs written by this version of streamlink-rs, see
+/// [`Config::version`](struct.Config.html#structfield.version).
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Upgrades the settings of older configs, along with the version each
+/// upgrades them to, in order. Version 1 only added the version itself, so
+/// configs without one are upgraded the same as version 1 ones.
+const MIGRATIONS: &[(u32, Migration)] = &[(2, move_youtube_key)];
+
+/// Upgrades the settings of a config of one version to the next.
+type Migration = fn(&mut Map<String, Value>);
+
+/// The version of a config, read on its own, so it's told even if the rest
+/// of a newer config can't be read.
+#[derive(Deserialize)]
+struct Versioned {
+    #[serde(default)]
+    version: u32,
+}
+
+/// What secrets are shown as, see [`Config::to_masked_toml`].
+///
+/// [`Config::to_masked_toml`]: struct.Config.html#method.to_masked_toml
+const MASK: &str = "********";
+
+/// How many configs deep includes may go.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Where the config is kept, within the config directory.
+const CONFIG_PATH: &str = "streamlink-rs/config.toml";
+
+/// What a new config starts out as, see [`Config::init_at`].
+///
+/// [`Config::init_at`]: struct.Config.html#method.init_at
+const STARTER_CONFIG: &str = r#"# streamlink-rs config, see https://github.com/Br1ght0ne/streamlink-rs
+version = 2
+
+# URLs of the streams to check.
+stream_urls = [
+    # "https://twitch.tv/northernlion",
+    # "https://www.youtube.com/channel/UC4PooiX37Pld1T8J5SYT-SQ",
+]
+
+# The backend checking stream status: "youtube-dl" or "streamlink".
+# backend = "youtube-dl"
+
+# How many streams to check at once.
+# concurrency = 4
+
+# How many times to retry a status check that failed.
+# retries = 0
+
+# How many seconds to reuse checked statuses for, or 0 to always check.
+# cache_ttl_secs = 0
+
+# How many seconds `strs --watch` waits between checking the streams.
+# poll_interval_secs = 300
+
+# The player to watch streams with, its arguments, and the quality to
+# watch them in.
+# player = "mpv"
+# player_args = []
+# quality = "best"
+
+# The directory `strs record` records streams into, and what it names them.
+# record_dir = "~/Videos"
+# record_name = "{name}-{date}"
+
+# Whether to show a desktop notification when a stream goes online, or
+# offline too.
+# notify = false
+# notify_offline = false
+
+# A webhook to post streams going online to, e.g. of Discord, and what to
+# post, with the placeholders of `--format`.
+# [webhook]
+# url = "https://discord.com/api/webhooks/..."
+# body = '{{"content": "{name} is {status}: {url}"}}'
+# on = ["online"]
+
+# Streams can also be given a name and tags, to check only some of them
+# with `--tag`.
+# [[streams]]
+# url = "https://twitch.tv/gogcom"
+# name = "GOG"
+# tags = ["games"]
+"#;
+
 impl Config {
+    /// Returns where the config is read from by default, e.g.
+    /// `$XDG_CONFIG_HOME/streamlink-rs/config.toml` on Linux,
+    /// `~/Library/Application Support/streamlink-rs/config.toml` on macOS,
+    /// or `%APPDATA%\streamlink-rs\config.toml` on Windows.
+    ///
+    /// A config already kept in `~/.config/streamlink-rs/config.toml` is
+    /// still used if there's none in the config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        default_path_in(dirs::config_dir(), dirs::home_dir())
+    }
+
+    /// Returns which config to read: `cli_arg` if given, or else the one in
+    /// the `STREAMLINK_RS_CONFIG` environment variable, or else `config.toml`
+    /// in the directory in `STREAMLINK_RS_CONFIG_DIR`, or else the
+    /// [default one](#method.default_path).
+    ///
+    /// Returns `None` only if there's no config directory to default to.
+    pub fn resolve_path(cli_arg: Option<&Path>) -> Option<PathBuf> {
+        Self::resolve_path_and_variable(cli_arg).map(|(path, _)| path)
+    }
+
+    /// Like [`resolve_path`](#method.resolve_path), but also returns the
+    /// environment variable the config was taken from, if any.
+    pub fn resolve_path_and_variable(
+        cli_arg: Option<&Path>,
+    ) -> Option<(PathBuf, Option<&'static str>)> {
+        resolve_path_with(cli_arg, |name| env::var_os(name), Self::default_path)
+    }
+
+    /// Creates a commented TOML config in `path` to start out with, along
+    /// with its directory.
+    ///
+    /// An existing file is never overwritten, and makes for a
+    /// [`ConfigExists`](enum.ErrorKind.html#variant.ConfigExists) error.
+    pub fn init_at<P: AsRef<Path>>(path: P) -> Result<()> {
+        let path = path.as_ref();
+        if Format::from_path(path)? != Format::Toml {
+            bail!(ErrorKind::UnsupportedConfigFormat(
+                path.extension()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into()
+            ));
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                bail!(ErrorKind::ConfigExists(path.display().to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        file.write_all(STARTER_CONFIG.as_bytes())?;
+        Ok(())
+    }
+
+    /// Reads the configs in `paths` in turn, each merged into the ones
+    /// before, as with [`merge`](#method.merge).
+    ///
+    /// Unlike with `merge`, any setting written in a later config overrides
+    /// the ones before, even if it's set back to its default.
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let mut configs = paths.iter().map(Self::new_with_settings);
+        let mut config = match configs.next() {
+            Some(config) => config?.0,
+            None => bail!("no config given"),
+        };
+        for other in configs {
+            let (other, _, written) = other?;
+            config = config.merge_settings(other, &written);
+        }
+        Ok(config)
+    }
+
+    /// Merges `other` into the config, as if `other` was read after it:
+    ///
+    /// - The streams of `other` are checked after the ones of the config,
+    ///   and streams with the same canonical URL only once. Of those, the
+    ///   name, quality, player and timeout of the last one setting them are
+    ///   used, the tags and backend arguments of all of them, and the stream
+    ///   is disabled if any of them is.
+    /// - The platforms, platform settings, and backend and player arguments
+    ///   of both are used, with the platforms and platform settings of
+    ///   `other` overriding the ones of the config.
+    /// - Any other setting of `other` overrides the config's, unless it's
+    ///   left unset or at its default, as it can't be told apart from one
+    ///   that isn't set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use streamlink::Config;
+    ///
+    /// let base = Config {
+    ///     stream_urls: vec!["https://twitch.tv/gogcom".into()],
+    ///     retries: 2,
+    ///     ..Config::default()
+    /// };
+    /// let overrides = Config {
+    ///     stream_urls: vec!["https://twitch.tv/GOGcom/".into()],
+    ///     concurrency: 8,
+    ///     ..Config::default()
+    /// };
+    /// let config = base.merge(overrides);
+    /// assert_eq!(vec!["https://twitch.tv/gogcom"], config.stream_urls);
+    /// assert_eq!((2, 8), (config.retries, config.concurrency));
+    /// ```
+    pub fn merge(self, other: Config) -> Config {
+        let written = changed_settings(&other);
+        self.merge_settings(other, &written)
+    }
+
+    /// Like [`merge`](#method.merge), but with the settings of `other` that
+    /// override the config's being the ones in `written`, e.g. the ones in
+    /// the file it's read from.
+    pub(crate) fn merge_settings(self, other: Config, written: &[String]) -> Config {
+        let written = |setting: &str| written.iter().any(|written| written == setting);
+        /// Picks `other` if it's written.
+        fn pick<T>(this: T, other: T, written: bool) -> T {
+            if written {
+                other
+            } else {
+                this
+            }
+        }
+        let mut platforms = self.platforms;
+        platforms.extend(other.platforms);
+        let mut platform = self.platform;
+        platform.extend(other.platform);
+        let allow_other = pick(self.allow_other, other.allow_other, written("allow_other"));
+        let mut registry = PlatformRegistry::new();
+        registry.register_config(&platforms);
+        let key = |url: &str| canonical_url(url, &registry, allow_other);
+
+        let mut stream_urls: Vec<String> = vec![];
+        for url in self.stream_urls.into_iter().chain(other.stream_urls) {
+            if !stream_urls
+                .iter()
+                .any(|existing| key(existing) == key(&url))
+            {
+                stream_urls.push(url);
+            }
+        }
+        let mut streams: Vec<StreamConfig> = vec![];
+        for stream in self.streams.into_iter().chain(other.streams) {
+            match streams
+                .iter_mut()
+                .find(|existing| key(&existing.url) == key(&stream.url))
+            {
+                Some(existing) => {
+                    if stream.name.is_some() {
+                        existing.name = stream.name;
+                    }
+                    for tag in stream.tags {
+                        if !existing.tags.contains(&tag) {
+                            existing.tags.push(tag);
+                        }
+                    }
+                    existing.enabled &= stream.enabled;
+                    if stream.quality.is_some() {
+                        existing.quality = stream.quality;
+                    }
+                    if stream.player.is_some() {
+                        existing.player = stream.player;
+                    }
+                    if stream.check_timeout_secs.is_some() {
+                        existing.check_timeout_secs = stream.check_timeout_secs;
+                    }
+                    existing.backend_args.extend(stream.backend_args);
+                    if stream.notify.is_some() {
+                        existing.notify = stream.notify;
+                    }
+                }
+                None => streams.push(stream),
+            }
+        }
+        let mut include = self.include;
+        include.extend(other.include);
+        let mut backend_args = self.backend_args;
+        backend_args.extend(other.backend_args);
+        let mut player_args = self.player_args;
+        player_args.extend(other.player_args);
+
+        Config {
+            version: self.version.max(other.version),
+            include,
+            stream_urls,
+            streams,
+            platforms,
+            platform,
+            allow_other,
+            backend: pick(self.backend, other.backend, written("backend")),
+            backend_programs: other.backend_programs.or(self.backend_programs),
+            backend_command: other.backend_command.or(self.backend_command),
+            backend_args,
+            cookies_file: other.cookies_file.or(self.cookies_file),
+            backend_batch_size: pick(
+                self.backend_batch_size,
+                other.backend_batch_size,
+                written("backend_batch_size"),
+            ),
+            check_timeout_secs: pick(
+                self.check_timeout_secs,
+                other.check_timeout_secs,
+                written("check_timeout_secs"),
+            ),
+            proxy: other.proxy.or(self.proxy),
+            retries: pick(self.retries, other.retries, written("retries")),
+            retry_delay_ms: pick(
+                self.retry_delay_ms,
+                other.retry_delay_ms,
+                written("retry_delay_ms"),
+            ),
+            min_interval_ms: pick(
+                self.min_interval_ms,
+                other.min_interval_ms,
+                written("min_interval_ms"),
+            ),
+            cache_ttl_secs: pick(
+                self.cache_ttl_secs,
+                other.cache_ttl_secs,
+                written("cache_ttl_secs"),
+            ),
+            poll_interval_secs: pick(
+                self.poll_interval_secs,
+                other.poll_interval_secs,
+                written("poll_interval_secs"),
+            ),
+            concurrency: pick(self.concurrency, other.concurrency, written("concurrency")),
+            color: pick(self.color, other.color, written("color")),
+            sort: pick(self.sort, other.sort, written("sort")),
+            group_by: pick(self.group_by, other.group_by, written("group_by")),
+            format: other.format.or(self.format),
+            player: pick(self.player, other.player, written("player")),
+            player_args,
+            quality: pick(self.quality, other.quality, written("quality")),
+            record_dir: other.record_dir.or(self.record_dir),
+            record_name: other.record_name.or(self.record_name),
+            notify: pick(self.notify, other.notify, written("notify")),
+            notify_offline: pick(
+                self.notify_offline,
+                other.notify_offline,
+                written("notify_offline"),
+            ),
+            webhook: other.webhook.or(self.webhook),
+            twitch: other.twitch.or(self.twitch),
+        }
+    }
+
+    /// Writes the config to `path`, in the format told by its extension,
+    /// creating its directory if needed.
+    ///
+    /// The file is replaced as a whole, so it's never left half-written.
+    /// Comments in it are not kept.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let contents = Format::from_path(path)?.write(self)?;
+        write_atomic(path, &contents)
+    }
+
+    /// Reads the config in `filepath`, in the format told by its extension:
+    /// `.toml`, `.json`, or with the `yaml` feature, `.yaml` or `.yml`, along
+    /// with the configs it [`include`](#structfield.include)s.
+    ///
+    /// A config that can't be parsed, e.g. because of a typo, makes for a
+    /// [`ConfigParse`](enum.ErrorKind.html#variant.ConfigParse) error telling
+    /// where the mistake is, and referring to an unset environment variable
+    /// an [`UnsetVariable`](enum.ErrorKind.html#variant.UnsetVariable) error
+    /// naming it.
     pub fn new<P>(filepath: P) -> Result<Self>
     where
         P: AsRef<Path>,
     {
-        let mut config = String::new();
-        let mut f = File::open(filepath)?;
-        f.read_to_string(&mut config).unwrap();
-        let config: Config = toml::from_str(config.as_str()).unwrap();
-        Ok(config)
+        Self::new_with_sources(filepath).map(|(config, _)| config)
+    }
+
+    /// Like [`new`](#method.new), but also returns what the config was read
+    /// from: the config itself, the configs it includes, and the environment
+    /// variables it refers to.
+    pub fn new_with_sources<P: AsRef<Path>>(filepath: P) -> Result<(Self, Vec<ConfigSource>)> {
+        Self::new_with_settings(filepath).map(|(config, sources, _)| (config, sources))
+    }
+
+    /// Like [`new_with_sources`](#method.new_with_sources), but also returns
+    /// the settings written in the config or the configs it includes, for
+    /// [`merge_settings`](#method.merge_settings).
+    pub(crate) fn new_with_settings<P: AsRef<Path>>(
+        filepath: P,
+    ) -> Result<(Self, Vec<ConfigSource>, Vec<String>)> {
+        let path = filepath.as_ref();
+        let mut sources = vec![ConfigSource::File(path.to_path_buf())];
+        let (mut config, include) = read(path)?;
+        let settings = if include.is_empty() {
+            read_value(path)?
+        } else {
+            // Read again, as merged with the included configs.
+            let merged = merge_includes(path, include, &mut vec![], &mut sources)?;
+            let include = config.include;
+            config = serde_json::from_value(Value::Object(merged.clone())).map_err(|e| {
+                ErrorKind::ConfigParse(path.display().to_string(), None, None, e.to_string())
+            })?;
+            config.include = include;
+            merged
+        };
+        let written = settings.into_iter().map(|(setting, _)| setting).collect();
+        let mut variables = vec![];
+        config.expand_variables_with(|name| {
+            if !variables.iter().any(|variable| variable == name) {
+                variables.push(name.to_string());
+            }
+            env::var(name).ok()
+        })?;
+        sources.extend(variables.into_iter().map(ConfigSource::Variable));
+        let cookies_files = config
+            .streams
+            .iter_mut()
+            .map(|stream| &mut stream.cookies_file)
+            .chain(Some(&mut config.cookies_file));
+        for cookies_file in cookies_files {
+            if let Some(path) = cookies_file.take() {
+                let path = expand_home(&path);
+                if !path.is_file() {
+                    bail!(ErrorKind::CookiesFileMissing(path.display().to_string()));
+                }
+                *cookies_file = Some(path);
+            }
+        }
+        config.record_dir = config.record_dir.map(|dir| expand_home(&dir));
+        let players = config
+            .streams
+            .iter()
+            .filter_map(|stream| stream.player.as_ref());
+        if Some(&config.player)
+            .into_iter()
+            .chain(players)
+            .any(|player| player.trim().is_empty())
+        {
+            bail!(ErrorKind::InvalidSetting(
+                "player".into(),
+                "the player can't be empty".into()
+            ));
+        }
+        if config.poll_interval_secs == 0 {
+            bail!(ErrorKind::InvalidSetting(
+                "poll_interval_secs".into(),
+                "the poll interval can't be 0".into()
+            ));
+        }
+        Ok((config, sources, written))
+    }
+
+    /// Like [`new`](#method.new), but also returns what's odd about the
+    /// config.
+    pub fn new_with_warnings<P: AsRef<Path>>(filepath: P) -> Result<(Self, Vec<ConfigWarning>)> {
+        let config = Self::new(filepath)?;
+        let warnings = config.warnings();
+        Ok((config, warnings))
+    }
+
+    /// Returns the config as TOML, as shown by `strs config show`, with the
+    /// Twitch client secret, API keys of the platforms, webhook URL and proxy
+    /// password masked.
+    pub fn to_masked_toml(&self) -> Result<String> {
+        let mut value = toml::Value::try_from(self).map_err(|e| e.to_string())?;
+        let secrets = [
+            ("twitch", "client_secret"),
+            // Webhook URLs carry their tokens, e.g. of Discord.
+            ("webhook", "url"),
+        ];
+        for &(table, key) in &secrets {
+            if let Some(secret) = value.get_mut(table).and_then(|table| table.get_mut(key)) {
+                *secret = MASK.into();
+            }
+        }
+        if let Some(platforms) = value.get_mut("platform").and_then(|p| p.as_table_mut()) {
+            for (_, settings) in platforms.iter_mut() {
+                if let Some(api_key) = settings.get_mut("api_key") {
+                    *api_key = MASK.into();
+                }
+            }
+        }
+        if let Some(proxy) = value.get_mut("proxy") {
+            let url = proxy.as_str().and_then(|proxy| Url::parse(proxy).ok());
+            if let Some(mut url) = url.filter(|url| url.password().is_some()) {
+                let _ = url.set_password(Some(MASK));
+                *proxy = url.as_str().into();
+            }
+        }
+        toml::to_string_pretty(&value).map_err(|e| e.to_string().into())
+    }
+
+    /// Returns what's odd about the config, e.g. streams configured twice, or
+    /// settings for unknown platforms.
+    pub fn warnings(&self) -> Vec<ConfigWarning> {
+        let mut registry = PlatformRegistry::new();
+        registry.register_config(&self.platforms);
+        let urls: Vec<String> = self
+            .stream_urls
+            .iter()
+            .chain(self.streams.iter().map(|stream| &stream.url))
+            .map(|url| canonical_url(url, &registry, self.allow_other))
+            .collect();
+        let mut warnings = vec![];
+        for (duplicate, url) in urls.iter().enumerate() {
+            let first = urls
+                .iter()
+                .position(|other| other == url)
+                .unwrap_or(duplicate);
+            if first < duplicate {
+                warnings.push(ConfigWarning::DuplicateStream {
+                    url: url.clone(),
+                    first: first + 1,
+                    duplicate: duplicate + 1,
+                });
+            }
+        }
+        let mut unknown: Vec<&String> = self
+            .platform
+            .keys()
+            .filter(|platform| !is_known_platform(platform))
+            .collect();
+        unknown.sort();
+        warnings.extend(
+            unknown
+                .into_iter()
+                .map(|platform| ConfigWarning::UnknownPlatform(platform.clone())),
+        );
+        let notify = self.notify
+            || self.notify_offline
+            || self
+                .streams
+                .iter()
+                .any(|stream| stream.notify == Some(true));
+        if notify && !cfg!(feature = "notifications") {
+            warnings.push(ConfigWarning::NotificationsUnavailable);
+        }
+        if self.webhook.is_some() && !cfg!(feature = "webhooks") {
+            warnings.push(ConfigWarning::WebhooksUnavailable);
+        }
+        let cookies = self.cookies_file.as_ref().or_else(|| {
+            self.streams
+                .iter()
+                .find_map(|stream| stream.cookies_file.as_ref())
+        });
+        if let Some(path) = cookies {
+            let backend = Backend::from_config(self);
+            if backend.cookies_args(path).is_empty() {
+                warnings.push(ConfigWarning::CookiesUnsupported(backend.id().into()));
+            }
+        }
+        warnings
+    }
+
+    /// Migrates the config in `path` to the current version in place, if it's
+    /// older, returning whether it was. The old config is kept next to it,
+    /// with `.bak` appended to its name.
+    ///
+    /// Comments in the config are not kept.
+    pub fn migrate_file<P: AsRef<Path>>(path: P) -> Result<bool> {
+        let path = path.as_ref();
+        let format = Format::from_path(path)?;
+        let contents = fs::read_to_string(path)
+            .chain_err(|| format!("unable to read config '{}'", path.display()))?;
+        let version = check_version(path, format, &contents)?;
+        if version == CONFIG_VERSION {
+            return Ok(false);
+        }
+        let (config, _) = read(path)?;
+        let mut backup = path.to_path_buf().into_os_string();
+        backup.push(".bak");
+        fs::copy(path, &backup)?;
+        config.save(path)?;
+        Ok(true)
+    }
+
+    /// Adds `stream` to the [`streams`](#structfield.streams).
+    ///
+    /// # Errors
+    ///
+    /// Fails if its URL isn't a stream, or with `DuplicateStream` if one of
+    /// the streams already has the same canonical URL.
+    pub fn add_stream(&mut self, stream: StreamConfig) -> Result<()> {
+        let added = Stream::from_string(stream.url.clone())?;
+        let urls = self
+            .stream_urls
+            .iter()
+            .chain(self.streams.iter().map(|stream| &stream.url));
+        for url in urls {
+            if Stream::from_string(url.clone()).ok().as_ref() == Some(&added) {
+                bail!(ErrorKind::DuplicateStream(stream.url, url.clone()));
+            }
+        }
+        self.streams.push(stream);
+        Ok(())
+    }
+
+    /// Adds each of `streams` like [`add_stream`](#method.add_stream),
+    /// skipping the ones with the canonical URL of a stream already in the
+    /// config, or of one added before.
+    ///
+    /// # Errors
+    ///
+    /// Fails if one of the URLs isn't a stream, in which case none are added.
+    pub fn import_streams(&mut self, streams: Vec<StreamConfig>) -> Result<ImportSummary> {
+        let mut imported = self.clone();
+        let mut summary = ImportSummary::default();
+        for stream in streams {
+            let url = stream.url.clone();
+            match imported.add_stream(stream) {
+                Ok(()) => summary.added.push(url),
+                Err(Error(ErrorKind::DuplicateStream(..), _)) => summary.skipped.push(url),
+                Err(e) => return Err(e),
+            }
+        }
+        *self = imported;
+        Ok(summary)
+    }
+
+    /// Removes the stream called `name_or_url`, by its
+    /// [`name`](struct.StreamConfig.html#structfield.name) or the one in its
+    /// URL, ignoring case, or at that URL, returning it.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `UnknownStream` if no stream is called that, or with
+    /// `AmbiguousStream` if more than one is.
+    pub fn remove_stream(&mut self, name_or_url: &str) -> Result<StreamConfig> {
+        let wanted = name_or_url.to_lowercase();
+        let wanted_stream = Stream::from_string(name_or_url.into()).ok();
+        let matches = |url: &str, alias: Option<&str>| {
+            if url == name_or_url || alias.map(str::to_lowercase).as_ref() == Some(&wanted) {
+                return true;
+            }
+            match Stream::from_string(url.into()) {
+                Ok(stream) => {
+                    stream.name().map(str::to_lowercase).as_ref() == Some(&wanted)
+                        || wanted_stream.as_ref() == Some(&stream)
+                }
+                Err(_) => false,
+            }
+        };
+        let in_urls: Vec<usize> = (0..self.stream_urls.len())
+            .filter(|&i| matches(&self.stream_urls[i], None))
+            .collect();
+        let in_streams: Vec<usize> = (0..self.streams.len())
+            .filter(|&i| matches(&self.streams[i].url, self.streams[i].name.as_deref()))
+            .collect();
+        match (in_urls.as_slice(), in_streams.as_slice()) {
+            (&[i], &[]) => Ok(StreamConfig::new(self.stream_urls.remove(i))),
+            (&[], &[i]) => Ok(self.streams.remove(i)),
+            (&[], &[]) => bail!(ErrorKind::UnknownStream(name_or_url.into(), None)),
+            _ => bail!(ErrorKind::AmbiguousStream(
+                name_or_url.into(),
+                in_urls
+                    .iter()
+                    .map(|&i| self.stream_urls[i].clone())
+                    .chain(in_streams.iter().map(|&i| {
+                        let stream = &self.streams[i];
+                        stream.name.clone().unwrap_or_else(|| stream.url.clone())
+                    }))
+                    .collect()
+            )),
+        }
+    }
+
+    /// Adds `stream` to the config in `path` like
+    /// [`add_stream`](#method.add_stream), saving it in place.
+    ///
+    /// Comments in the config are not kept.
+    pub fn add_stream_to_file<P: AsRef<Path>>(path: P, stream: StreamConfig) -> Result<()> {
+        Self::edit_file(path.as_ref(), |config| config.add_stream(stream))
+    }
+
+    /// Adds `streams` to the config in `path` like
+    /// [`import_streams`](#method.import_streams), saving it in place.
+    ///
+    /// Comments in the config are not kept.
+    pub fn import_streams_to_file<P: AsRef<Path>>(
+        path: P,
+        streams: Vec<StreamConfig>,
+    ) -> Result<ImportSummary> {
+        Self::edit_file(path.as_ref(), |config| config.import_streams(streams))
+    }
+
+    /// Removes the stream called `name_or_url` from the config in `path` like
+    /// [`remove_stream`](#method.remove_stream), saving it in place.
+    ///
+    /// Comments in the config are not kept.
+    pub fn remove_stream_from_file<P: AsRef<Path>>(
+        path: P,
+        name_or_url: &str,
+    ) -> Result<StreamConfig> {
+        Self::edit_file(path.as_ref(), |config| config.remove_stream(name_or_url))
+    }
+
+    /// Applies `edit` to the config in `path` as written, without the configs
+    /// it includes or its variables expanded, and saves it if that succeeds.
+    fn edit_file<T, F>(path: &Path, edit: F) -> Result<T>
+    where
+        F: FnOnce(&mut Config) -> Result<T>,
+    {
+        let (mut config, _) = read(path)?;
+        let edited = edit(&mut config)?;
+        config
+            .save(path)
+            .chain_err(|| ErrorKind::ConfigWrite(path.display().to_string()))?;
+        Ok(edited)
+    }
+
+    /// Returns the player to watch `stream` with: the one set for the stream
+    /// in [`streams`](#structfield.streams), if any, or else
+    /// [`player`](#structfield.player). It's passed
+    /// [`player_args`](#structfield.player_args).
+    pub fn player_for(&self, stream: &Stream) -> &str {
+        self.stream_config(stream)
+            .and_then(|config| config.player.as_deref())
+            .unwrap_or(&self.player)
+    }
+
+    /// Returns the quality to watch `stream` in: the one set for the stream
+    /// in [`streams`](#structfield.streams), if any, or else
+    /// [`quality`](#structfield.quality).
+    pub fn quality_for(&self, stream: &Stream) -> &str {
+        self.stream_config(stream)
+            .and_then(|config| config.quality.as_deref())
+            .unwrap_or(&self.quality)
+    }
+
+    /// Returns whether to notify of `stream` going online: as set for the
+    /// stream in [`streams`](#structfield.streams), if any, or else as
+    /// [`notify`](#structfield.notify).
+    pub fn notify_for(&self, stream: &Stream) -> bool {
+        self.stream_config(stream)
+            .and_then(|config| config.notify)
+            .unwrap_or(self.notify)
+    }
+
+    /// Returns the settings for the streams on `platform`, as set in
+    /// [`platform`](#structfield.platform).
+    pub fn platform_settings(&self, platform: PlatformKind) -> Option<&PlatformSettings> {
+        self.platform.get(platform.id())
+    }
+
+    /// Returns how many seconds a backend may take to check `stream`: as set
+    /// for the stream in [`streams`](#structfield.streams), if any, or else
+    /// for its platform in [`platform`](#structfield.platform), or else
+    /// [`check_timeout_secs`](#structfield.check_timeout_secs).
+    pub fn check_timeout_for(&self, stream: &Stream) -> u64 {
+        self.stream_config(stream)
+            .and_then(|config| config.check_timeout_secs)
+            .or_else(|| {
+                self.platform_settings(stream.platform())
+                    .and_then(|settings| settings.check_timeout_secs)
+            })
+            .unwrap_or(self.check_timeout_secs)
+    }
+
+    /// Returns the arguments passed to the backend to check `stream` after
+    /// [`backend_args`](#structfield.backend_args): the ones set for its
+    /// platform in [`platform`](#structfield.platform), followed by the ones
+    /// set for the stream in [`streams`](#structfield.streams), and the ones
+    /// sending its own cookies file, if it has one.
+    pub fn extra_backend_args_for(&self, stream: &Stream) -> Vec<String> {
+        let platform = self
+            .platform_settings(stream.platform())
+            .map(|settings| &settings.backend_args[..])
+            .unwrap_or_default();
+        let own = self.stream_config(stream);
+        let mut args: Vec<String> = platform
+            .iter()
+            .chain(
+                own.map(|config| &config.backend_args[..])
+                    .unwrap_or_default(),
+            )
+            .cloned()
+            .collect();
+        // Passed last, so they take precedence over the global cookies file.
+        if let Some(path) = own.and_then(|config| config.cookies_file.as_ref()) {
+            args.extend(Backend::from_config(self).cookies_args(path));
+        }
+        args
+    }
+
+    /// Returns how `stream` is set in [`streams`](#structfield.streams).
+    fn stream_config(&self, stream: &Stream) -> Option<&StreamConfig> {
+        let mut registry = PlatformRegistry::new();
+        registry.register_config(&self.platforms);
+        let url = stream.canonical_url().as_str();
+        self.streams
+            .iter()
+            .find(|config| canonical_url(&config.url, &registry, self.allow_other) == url)
+    }
+
+    /// Expands the environment variables the config refers to, reading them
+    /// with `var`.
+    pub(crate) fn expand_variables_with<F>(&mut self, mut var: F) -> Result<()>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        for url in &mut self.stream_urls {
+            *url = expand_variables("stream_urls", url, &mut var)?;
+        }
+        for stream in &mut self.streams {
+            stream.url = expand_variables("streams.url", &stream.url, &mut var)?;
+        }
+        if let Some(ref mut proxy) = self.proxy {
+            *proxy = expand_variables("proxy", proxy, &mut var)?;
+        }
+        if let Some(ref mut path) = self.cookies_file {
+            if let Some(expanded) = path.to_str() {
+                let expanded = expand_variables("cookies_file", expanded, &mut var)?;
+                *path = expanded.into();
+            }
+        }
+        for stream in &mut self.streams {
+            if let Some(ref mut path) = stream.cookies_file {
+                if let Some(expanded) = path.to_str() {
+                    let expanded = expand_variables("streams.cookies_file", expanded, &mut var)?;
+                    *path = expanded.into();
+                }
+            }
+        }
+        if let Some(ref mut twitch) = self.twitch {
+            twitch.client_id = expand_variables("twitch.client_id", &twitch.client_id, &mut var)?;
+            twitch.client_secret =
+                expand_variables("twitch.client_secret", &twitch.client_secret, &mut var)?;
+        }
+        for (platform, settings) in &mut self.platform {
+            if let Some(ref mut api_key) = settings.api_key {
+                let setting = format!("platform.{}.api_key", platform);
+                *api_key = expand_variables(&setting, api_key, &mut var)?;
+            }
+        }
+        if let Some(ref mut webhook) = self.webhook {
+            webhook.url = expand_variables("webhook.url", &webhook.url, &mut var)?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces each `${NAME}` in the `setting`'s `value` with the environment
+/// variable `NAME`, as read with `var`, and each `$$` with a `$`. Any other
+/// `$` is kept as is.
+fn expand_variables<F>(setting: &str, value: &str, var: &mut F) -> Result<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$$") {
+            expanded.push('$');
+            rest = after;
+            continue;
+        }
+        let end = match rest.find('}') {
+            Some(end) if rest.starts_with("${") => end,
+            _ => {
+                expanded.push('$');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let name = &rest[2..end];
+        match var(name) {
+            Some(value) => expanded.push_str(&value),
+            None => bail!(ErrorKind::UnsetVariable(setting.into(), name.into())),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Reads the config in `path`, along with the paths of the configs it
+/// includes.
+fn read(path: &Path) -> Result<(Config, Vec<PathBuf>)> {
+    let format = Format::from_path(path)?;
+    let contents = fs::read_to_string(path)
+        .chain_err(|| format!("unable to read config '{}'", path.display()))?;
+    let parse_error = |e: ParseError| {
+        let (line, column) = match e.position {
+            Some((line, column)) => (Some(line), Some(column)),
+            None => (None, None),
+        };
+        Error::from(ErrorKind::ConfigParse(
+            path.display().to_string(),
+            line,
+            column,
+            e.message,
+        ))
+    };
+    // Parsed as a config first, so mistakes are told where they are.
+    let mut config: Config = match format.parse(&contents) {
+        Ok(config) => config,
+        Err(e) => {
+            // Newer configs may not be read at all.
+            check_version(path, format, &contents)?;
+            return Err(parse_error(e));
+        }
+    };
+    if check_version(path, format, &contents)? < CONFIG_VERSION {
+        let mut settings = format.parse(&contents).map_err(parse_error)?;
+        migrate(path, &mut settings)?;
+        config = serde_json::from_value(Value::Object(settings)).map_err(|e| {
+            ErrorKind::ConfigParse(path.display().to_string(), None, None, e.to_string())
+        })?;
+    }
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let include = config
+        .include
+        .iter()
+        .map(|include| dir.join(expand_home(include)))
+        .collect();
+    Ok((config, include))
+}
+
+/// Reads the config in `path` as merged with the configs it `include`s, in
+/// order, and the ones they include in turn.
+///
+/// `chain` holds the configs including it, to tell include cycles, and the
+/// included configs are added to `sources`.
+fn merge_includes(
+    path: &Path,
+    include: Vec<PathBuf>,
+    chain: &mut Vec<PathBuf>,
+    sources: &mut Vec<ConfigSource>,
+) -> Result<Map<String, Value>> {
+    let canonical = fs::canonicalize(path)
+        .chain_err(|| format!("unable to read config '{}'", path.display()))?;
+    if chain.contains(&canonical) {
+        let cycle: Vec<String> = chain
+            .iter()
+            .skip_while(|including| **including != canonical)
+            .chain(Some(&canonical))
+            .map(|path| path.display().to_string())
+            .collect();
+        bail!(ErrorKind::IncludeCycle(cycle.join(" -> ")));
+    }
+    if chain.len() >= MAX_INCLUDE_DEPTH {
+        bail!(ErrorKind::IncludeTooDeep(
+            path.display().to_string(),
+            MAX_INCLUDE_DEPTH
+        ));
+    }
+    chain.push(canonical);
+    let mut merged = Map::new();
+    for included in include {
+        sources.push(ConfigSource::Include {
+            path: included.clone(),
+            by: path.to_path_buf(),
+        });
+        let (_, include) = read(&included)?;
+        merge(
+            &mut merged,
+            merge_includes(&included, include, chain, sources)?,
+        );
+    }
+    merge(&mut merged, read_value(path)?);
+    chain.pop();
+    Ok(merged)
+}
+
+/// Reads the settings in the config in `path`, as they're written.
+fn read_value(path: &Path) -> Result<Map<String, Value>> {
+    let contents = fs::read_to_string(path)
+        .chain_err(|| format!("unable to read config '{}'", path.display()))?;
+    let mut settings = Format::from_path(path)?
+        .parse(&contents)
+        .map_err(|e| ErrorKind::ConfigParse(path.display().to_string(), None, None, e.message))?;
+    migrate(path, &mut settings)?;
+    settings.remove("include");
+    Ok(settings)
+}
+
+/// Returns the version of the config in `path`, as read from `contents`,
+/// unless it's newer than this version of streamlink-rs can read.
+fn check_version(path: &Path, format: Format, contents: &str) -> Result<u32> {
+    let version = format
+        .parse::<Versioned>(contents)
+        .map_or(0, |versioned| versioned.version);
+    if version > CONFIG_VERSION {
+        bail!(ErrorKind::ConfigTooNew(path.display().to_string(), version));
+    }
+    Ok(version)
+}
+
+/// Upgrades the `settings` of the config in `path` to the current version.
+fn migrate(path: &Path, settings: &mut Map<String, Value>) -> Result<()> {
+    let version = match settings.get("version") {
+        Some(version) => version.as_u64().unwrap_or(0) as u32,
+        None => 0,
+    };
+    if version > CONFIG_VERSION {
+        bail!(ErrorKind::ConfigTooNew(path.display().to_string(), version));
+    }
+    for &(upgraded, migration) in MIGRATIONS {
+        if version < upgraded {
+            migration(settings);
+        }
+    }
+    settings.insert("version".into(), CONFIG_VERSION.into());
+    Ok(())
+}
+
+/// Migrates version 1 configs, which kept the YouTube API key in a
+/// `[youtube]` table, to version 2, where it's the `api_key` of
+/// `[platform.youtube]`. A key already set there is kept.
+fn move_youtube_key(settings: &mut Map<String, Value>) {
+    let api_key = match settings.remove("youtube") {
+        Some(Value::Object(mut youtube)) => youtube.remove("api_key"),
+        _ => None,
+    };
+    if let Some(api_key) = api_key {
+        let platforms = settings
+            .entry("platform")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(ref mut platforms) = *platforms {
+            let youtube = platforms
+                .entry("youtube")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(ref mut youtube) = *youtube {
+                youtube.entry("api_key").or_insert(api_key);
+            }
+        }
+    }
+}
+
+/// Returns the settings of `config` that aren't at their default.
+fn changed_settings(config: &Config) -> Vec<String> {
+    let settings = |config: &Config| match serde_json::to_value(config) {
+        Ok(Value::Object(settings)) => settings,
+        _ => Map::new(),
+    };
+    let defaults = settings(&Config::default());
+    settings(config)
+        .into_iter()
+        .filter(|(setting, value)| defaults.get(setting) != Some(value))
+        .map(|(setting, _)| setting)
+        .collect()
+}
+
+/// Merges `settings` into `merged`, adding to the streams, platforms and
+/// platform settings, and overriding the rest.
+fn merge(merged: &mut Map<String, Value>, settings: Map<String, Value>) {
+    for (key, value) in settings {
+        match (merged.get_mut(&key), value) {
+            (Some(&mut Value::Array(ref mut streams)), Value::Array(more))
+                if key == "stream_urls" || key == "streams" =>
+            {
+                streams.extend(more)
+            }
+            (Some(&mut Value::Object(ref mut platforms)), Value::Object(more))
+                if key == "platforms" || key == "platform" =>
+            {
+                platforms.extend(more)
+            }
+            (_, value) => {
+                merged.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns the default config path with `config_dir` as the config
+/// directory, and `home_dir` as the home directory.
+pub(crate) fn default_path_in(
+    config_dir: Option<PathBuf>,
+    home_dir: Option<PathBuf>,
+) -> Option<PathBuf> {
+    let current = config_dir.map(|dir| dir.join(CONFIG_PATH));
+    let legacy = home_dir.map(|dir| dir.join(".config").join(CONFIG_PATH));
+    match (current, legacy) {
+        (Some(current), _) if current.is_file() => Some(current),
+        (_, Some(legacy)) if legacy.is_file() => Some(legacy),
+        (current, legacy) => current.or(legacy),
+    }
+}
+
+/// Like [`Config::resolve_path_and_variable`], but reads the environment
+/// variables with `var`, and defaults to what `default_path` returns.
+///
+/// [`Config::resolve_path_and_variable`]: struct.Config.html#method.resolve_path_and_variable
+pub(crate) fn resolve_path_with<F, D>(
+    cli_arg: Option<&Path>,
+    var: F,
+    default_path: D,
+) -> Option<(PathBuf, Option<&'static str>)>
+where
+    F: Fn(&str) -> Option<OsString>,
+    D: FnOnce() -> Option<PathBuf>,
+{
+    let var = |name| var(name).filter(|value: &OsString| !value.is_empty());
+    cli_arg
+        .map(|path| (path.to_path_buf(), None))
+        .or_else(|| {
+            var("STREAMLINK_RS_CONFIG").map(|path| (path.into(), Some("STREAMLINK_RS_CONFIG")))
+        })
+        .or_else(|| {
+            var("STREAMLINK_RS_CONFIG_DIR").map(|dir| {
+                let path = PathBuf::from(dir).join("config.toml");
+                (path, Some("STREAMLINK_RS_CONFIG_DIR"))
+            })
+        })
+        .or_else(|| default_path().map(|path| (path, None)))
+}
+
+/// Returns the canonical URL of the stream on `url`, or `url` itself if it
+/// isn't one, to tell streams apart.
+fn canonical_url(url: &str, registry: &PlatformRegistry, allow_other: bool) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|parsed| Stream::from_url_with_options(parsed, registry, allow_other).ok())
+        .map_or_else(|| url.into(), |stream| stream.canonical_url().to_string())
+}
+
+/// Replaces a leading `~` in `path` with the home directory.
+pub(crate) fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
     }
 }
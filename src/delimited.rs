@@ -0,0 +1,88 @@
+use {format_timestamp, StatusReport};
+
+/// The columns of CSV and TSV output, in order.
+const COLUMNS: [&str; 6] = ["name", "url", "platform", "status", "title", "checked_at"];
+
+/// What separates the fields of each row of delimited output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Delimiter {
+    /// Commas, with fields quoted as told by RFC 4180.
+    Comma,
+    /// Tabs, with tabs, line breaks and backslashes in fields escaped with
+    /// backslashes, e.g. `\t`.
+    Tab,
+}
+
+/// Formats `reports` as a header row of the [`COLUMNS`], followed by a row
+/// per stream, separated by `delimiter`. Titles and times that aren't known
+/// are empty, and `checked_at` is as e.g. `2024-01-31T12:00:00Z`.
+pub(crate) fn render_delimited(reports: &[StatusReport], delimiter: Delimiter) -> String {
+    let mut rows = String::new();
+    push_row(
+        &mut rows,
+        delimiter,
+        COLUMNS.iter().map(|&column| column.into()),
+    );
+    for report in reports {
+        let fields = vec![
+            report.name.clone(),
+            report.url.clone(),
+            report.platform.clone(),
+            report.status.to_string(),
+            report.title.clone().unwrap_or_default(),
+            report.checked_at.map(format_timestamp).unwrap_or_default(),
+        ];
+        push_row(&mut rows, delimiter, fields);
+    }
+    rows
+}
+
+/// Appends a row of `fields` to `rows`, escaped for `delimiter`.
+fn push_row<I>(rows: &mut String, delimiter: Delimiter, fields: I)
+where
+    I: IntoIterator<Item = String>,
+{
+    for (i, field) in fields.into_iter().enumerate() {
+        match delimiter {
+            Delimiter::Comma => {
+                if i > 0 {
+                    rows.push(',');
+                }
+                rows.push_str(&quote_csv(&field));
+            }
+            Delimiter::Tab => {
+                if i > 0 {
+                    rows.push('\t');
+                }
+                rows.push_str(&escape_tsv(&field));
+            }
+        }
+    }
+    rows.push('\n');
+}
+
+/// Quotes `field` if it has a comma, quote or line break in it, doubling
+/// its quotes.
+fn quote_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.into()
+    }
+}
+
+/// Escapes the tabs, line breaks and backslashes in `field`, which would
+/// otherwise split it.
+fn escape_tsv(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,296 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use ansi_term::Colour::{Green, Red, Yellow};
+use url::Url;
+
+use errors::*;
+use record::check_writable;
+use {paint, Backend, Config, PlatformRegistry, StatusHistory, Stream};
+
+/// The outcome of one of the checks of [`doctor`](fn.doctor.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    /// What was checked, e.g. `"backend"`.
+    pub subject: String,
+    /// Whether it's set up right.
+    pub passed: bool,
+    /// What was found, e.g. the version of the backend, or else what's
+    /// wrong.
+    pub detail: String,
+    /// What's odd, but still works, e.g. the warnings of a config.
+    pub warnings: Vec<String>,
+}
+
+impl Probe {
+    fn pass<S: Into<String>>(subject: &str, detail: S) -> Self {
+        Probe {
+            subject: subject.into(),
+            passed: true,
+            detail: detail.into(),
+            warnings: vec![],
+        }
+    }
+
+    fn fail<S: Into<String>>(subject: &str, detail: S) -> Self {
+        Probe {
+            passed: false,
+            ..Probe::pass(subject, detail)
+        }
+    }
+
+    /// Formats the probe as e.g. `pass  backend: yt-dlp 2024.01.01`, with a
+    /// line for each of its warnings after it.
+    fn lines(&self, color: bool) -> String {
+        let mark = if self.passed {
+            paint(color, Green, "pass".into())
+        } else {
+            paint(color, Red, "fail".into())
+        };
+        let mut lines = format!("{}  {}: {}\n", mark, self.subject, self.detail);
+        for warning in &self.warnings {
+            let warning = paint(color, Yellow, format!("warning: {}", warning));
+            lines.push_str(&format!("      {}\n", warning));
+        }
+        lines
+    }
+}
+
+/// Checks that the configs in `config_paths` and what they use are set up
+/// right, printing a line for each check, and returns whether they all
+/// passed. See [`diagnose`](fn.diagnose.html) for what's checked.
+pub fn doctor<P: AsRef<Path>>(config_paths: &[P], color: bool) -> Result<bool> {
+    doctor_to(config_paths, color, &mut io::stdout())
+}
+
+/// Like [`doctor`](fn.doctor.html), but writes to `out` instead of stdout.
+pub fn doctor_to<P, W>(config_paths: &[P], color: bool, out: &mut W) -> Result<bool>
+where
+    P: AsRef<Path>,
+    W: Write,
+{
+    let probes = diagnose(config_paths);
+    for probe in &probes {
+        write!(out, "{}", probe.lines(color))?;
+    }
+    let failed = probes.iter().filter(|probe| !probe.passed).count();
+    if failed == 0 {
+        writeln!(out, "all {} checks passed", probes.len())?;
+    } else {
+        writeln!(out, "{} of {} checks failed", failed, probes.len())?;
+    }
+    Ok(failed == 0)
+}
+
+/// Checks that the configs in `config_paths` exist and parse, that the
+/// streams in them are valid, that the backend and players they set are
+/// installed, and that the cache and record directories can be written to.
+///
+/// What depends on the configs is only checked if at least one of them
+/// parses.
+pub fn diagnose<P: AsRef<Path>>(config_paths: &[P]) -> Vec<Probe> {
+    let mut probes = vec![];
+    let mut merged: Option<Config> = None;
+    for path in config_paths {
+        let (probe, config) = probe_config(path.as_ref());
+        probes.push(probe);
+        if let Some((config, written)) = config {
+            merged = Some(match merged {
+                Some(merged) => merged.merge_settings(config, &written),
+                None => config,
+            });
+        }
+    }
+    if let Some(ref config) = merged {
+        probes.extend(probe_streams(config));
+        probes.push(probe_backend(&Backend::from_config(config)));
+        probes.extend(probe_players(config, env::var_os("PATH").as_deref()));
+    }
+    // The history is kept there, even with the cache turned off.
+    let cache_dir = StatusHistory::default_path()
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf);
+    if let Some(dir) = cache_dir {
+        probes.push(probe_dir("cache directory", &dir));
+    }
+    if let Some(dir) = merged.and_then(|config| config.record_dir) {
+        probes.push(probe_dir("record directory", &dir));
+    }
+    probes
+}
+
+/// Checks that the config at `path` exists and parses, returning it and the
+/// settings written in it if it does.
+pub(crate) fn probe_config(path: &Path) -> (Probe, Option<(Config, Vec<String>)>) {
+    let subject = "config";
+    if !path.is_file() {
+        let detail = format!(
+            "no config found at '{}', create one with `strs init`",
+            path.display()
+        );
+        return (Probe::fail(subject, detail), None);
+    }
+    match Config::new_with_settings(path) {
+        Ok((config, _, written)) => {
+            let mut probe = Probe::pass(subject, format!("'{}'", path.display()));
+            probe.warnings = config.warnings().iter().map(ToString::to_string).collect();
+            (probe, Some((config, written)))
+        }
+        Err(e) => (Probe::fail(subject, e.to_string()), None),
+    }
+}
+
+/// Checks that each of the URLs in `config` is a stream, failing for each
+/// that isn't, or else passing once for all of them.
+pub(crate) fn probe_streams(config: &Config) -> Vec<Probe> {
+    let mut registry = PlatformRegistry::new();
+    registry.register_config(&config.platforms);
+    let urls = config
+        .stream_urls
+        .iter()
+        .chain(config.streams.iter().map(|stream| &stream.url));
+    let mut valid = 0;
+    let mut probes = vec![];
+    for url in urls {
+        let stream = Url::parse(url)
+            .chain_err(|| ErrorKind::UrlParse(url.clone()))
+            .and_then(|parsed| {
+                Stream::from_url_with_options(parsed, &registry, config.allow_other)
+            });
+        match stream {
+            Ok(_) => valid += 1,
+            Err(e) => probes.push(Probe::fail(&format!("stream '{}'", url), e.to_string())),
+        }
+    }
+    if probes.is_empty() {
+        let detail = match valid {
+            1 => "the 1 configured stream is valid".into(),
+            valid => format!("all {} configured streams are valid", valid),
+        };
+        probes.push(Probe::pass("streams", detail));
+    }
+    probes
+}
+
+/// Checks that one of the programs of `backend` is installed, by running the
+/// first one found with `--version`, and tells its version.
+pub(crate) fn probe_backend(backend: &Backend) -> Probe {
+    let subject = "backend";
+    for program in backend.programs() {
+        let output = Command::new(program)
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output();
+        let output = match output {
+            Ok(output) => output,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Probe::fail(subject, format!("unable to run '{}': {}", program, e)),
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let detail = match first_line(&stderr) {
+                Some(line) => format!("'{} --version' {}: {}", program, output.status, line),
+                None => format!("'{} --version' {}", program, output.status),
+            };
+            return Probe::fail(subject, detail);
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = match first_line(&stdout) {
+            // `streamlink` tells its name itself, `yt-dlp` just the version.
+            Some(version) if version.starts_with(program.as_str()) => version.into(),
+            Some(version) => format!("{} {}", program, version),
+            None => format!("{}, of an unknown version", program),
+        };
+        return Probe::pass(subject, detail);
+    }
+    let missing = ErrorKind::BackendMissing(backend.programs().join(", "));
+    Probe::fail(subject, Error::from(missing).to_string())
+}
+
+/// Checks that the players set in `config`, for all the streams or some of
+/// them, are in one of the directories of `path`, e.g. `PATH`, telling where.
+pub(crate) fn probe_players(config: &Config, path: Option<&OsStr>) -> Vec<Probe> {
+    let mut players: Vec<&str> = vec![&config.player];
+    for stream in &config.streams {
+        if let Some(ref player) = stream.player {
+            if !players.contains(&player.as_str()) {
+                players.push(player);
+            }
+        }
+    }
+    players
+        .into_iter()
+        .map(|player| match find_program(player, path) {
+            Some(found) => Probe::pass("player", format!("'{}'", found.display())),
+            None => {
+                let missing = ErrorKind::PlayerMissing(player.into());
+                Probe::fail("player", Error::from(missing).to_string())
+            }
+        })
+        .collect()
+}
+
+/// Checks that files can be written to `dir`, or that it can be created if
+/// it doesn't exist yet, without creating it.
+pub(crate) fn probe_dir(subject: &str, dir: &Path) -> Probe {
+    if dir.is_dir() {
+        return match check_writable(dir) {
+            Ok(()) => Probe::pass(subject, format!("'{}' is writable", dir.display())),
+            Err(e) => Probe::fail(
+                subject,
+                format!("'{}' isn't writable: {}", dir.display(), e),
+            ),
+        };
+    }
+    if dir.exists() {
+        return Probe::fail(subject, format!("'{}' isn't a directory", dir.display()));
+    }
+    // Whatever is created is created in the closest directory that exists.
+    let parent = dir.ancestors().skip(1).find(|parent| parent.exists());
+    match parent {
+        Some(parent) if parent.is_dir() && check_writable(parent).is_ok() => Probe::pass(
+            subject,
+            format!("'{}' doesn't exist yet, but can be created", dir.display()),
+        ),
+        _ => Probe::fail(
+            subject,
+            format!("'{}' doesn't exist, and can't be created", dir.display()),
+        ),
+    }
+}
+
+/// Returns where `program` is, if it's a file that can be run: as is if it's
+/// a path, or else in one of the directories of `path`.
+fn find_program(program: &str, path: Option<&OsStr>) -> Option<PathBuf> {
+    let program = Path::new(program);
+    if program.components().count() > 1 {
+        return Some(program.to_path_buf()).filter(|program| is_executable(program));
+    }
+    env::split_paths(path?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// Returns the first line of `text` that isn't blank, trimmed.
+fn first_line(text: &str) -> Option<&str> {
+    text.lines().map(str::trim).find(|line| !line.is_empty())
+}
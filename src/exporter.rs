@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use indicatif::ProgressBar;
+
+use errors::*;
+use {check_reports, Run, RunOptions, StatusReport, StreamStatus};
+
+/// What [`export`](fn.export.html) listens on by default.
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:9184";
+
+/// Checks the streams in the configs in `config_paths` over and over, as
+/// with [`poll`](fn.poll.html), and serves their statuses as Prometheus
+/// metrics at `/metrics` on `addr`, e.g. `127.0.0.1:9184`. It only returns
+/// on errors.
+///
+/// The metrics are `stream_online`, 1 or 0 for each stream that could be
+/// checked, `stream_check_errors_total`, counting the checks of each stream
+/// that failed, both labelled by its `name` and `platform`, and
+/// `stream_check_duration_seconds`, how long the last checks took in all.
+pub fn export<P: AsRef<Path>>(config_paths: &[P], options: &RunOptions, addr: &str) -> Result<()> {
+    let listener = TcpListener::bind(addr).chain_err(|| ErrorKind::ExporterListen(addr.into()))?;
+    export_on(listener, config_paths, options, None)
+}
+
+/// Like [`export`](fn.export.html), but serves on `listener`, and checks the
+/// streams only `cycles` times, if given. The last metrics are still served
+/// after it returns.
+pub fn export_on<P: AsRef<Path>>(
+    listener: TcpListener,
+    config_paths: &[P],
+    options: &RunOptions,
+    cycles: Option<usize>,
+) -> Result<()> {
+    let mut run = Run::new(config_paths, options)?;
+    let metrics = Arc::new(Mutex::new(Metrics::default()));
+    let served = Arc::clone(&metrics);
+    thread::spawn(move || serve(&listener, &served));
+    let check = |run: &Run| {
+        check_reports(
+            &run.streamlink,
+            run.concurrency,
+            &ProgressBar::hidden(),
+            |_| Ok(()),
+        )
+    };
+    run.poll(options, cycles, check, |_, reports, elapsed| {
+        lock(&metrics).update(&reports, elapsed);
+        Ok(())
+    })
+}
+
+/// What's known of the checks so far, served as metrics.
+#[derive(Debug, Default)]
+struct Metrics {
+    /// The statuses of the last checks.
+    reports: Vec<StatusReport>,
+    /// How long the last checks took in all.
+    duration: Option<Duration>,
+    /// How many checks of each stream failed, by its canonical URL.
+    errors: HashMap<String, u64>,
+}
+
+impl Metrics {
+    /// Takes in the statuses of checks that took `duration` in all.
+    fn update(&mut self, reports: &[StatusReport], duration: Duration) {
+        for report in reports {
+            let errors = self.errors.entry(report.url.clone()).or_insert(0);
+            if report.status == StreamStatus::Unknown {
+                *errors += 1;
+            }
+        }
+        self.reports = reports.to_vec();
+        self.duration = Some(duration);
+    }
+
+    /// Formats the metrics in the Prometheus text exposition format.
+    fn render(&self) -> String {
+        let mut text = String::new();
+        text.push_str("# HELP stream_online Whether the stream was online when last checked.\n");
+        text.push_str("# TYPE stream_online gauge\n");
+        for report in &self.reports {
+            let online = match report.status {
+                StreamStatus::Online => 1,
+                StreamStatus::Offline => 0,
+                // Neither is known, which the errors tell.
+                StreamStatus::Unknown => continue,
+            };
+            text.push_str(&format!("stream_online{} {}\n", labels(report), online));
+        }
+        text.push_str("# HELP stream_check_errors_total How many checks of the stream failed.\n");
+        text.push_str("# TYPE stream_check_errors_total counter\n");
+        for report in &self.reports {
+            let errors = self.errors.get(&report.url).cloned().unwrap_or(0);
+            text.push_str(&format!(
+                "stream_check_errors_total{} {}\n",
+                labels(report),
+                errors
+            ));
+        }
+        text.push_str(
+            "# HELP stream_check_duration_seconds How long the last checks of the streams took.\n",
+        );
+        text.push_str("# TYPE stream_check_duration_seconds gauge\n");
+        if let Some(duration) = self.duration {
+            text.push_str(&format!(
+                "stream_check_duration_seconds {}\n",
+                duration.as_secs_f64()
+            ));
+        }
+        text
+    }
+}
+
+/// Formats the labels of the stream in `report`, e.g.
+/// `{name="gogcom",platform="twitch"}`.
+fn labels(report: &StatusReport) -> String {
+    format!(
+        "{{name=\"{}\",platform=\"{}\"}}",
+        escape_label(&report.name),
+        escape_label(&report.platform)
+    )
+}
+
+/// Escapes the backslashes, quotes and line breaks in the label value
+/// `value`.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn lock(metrics: &Mutex<Metrics>) -> MutexGuard<'_, Metrics> {
+    metrics.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Answers the requests coming in on `listener` with `metrics`, one at a
+/// time, which is plenty for a scraper.
+fn serve(listener: &TcpListener, metrics: &Mutex<Metrics>) {
+    for stream in listener.incoming().flatten() {
+        // A client hanging up early only fails its own request.
+        let _ = respond(stream, metrics);
+    }
+}
+
+/// Reads the HTTP request on `stream`, and answers it with `metrics` if it's
+/// for `/metrics`.
+fn respond(mut stream: TcpStream, metrics: &Mutex<Metrics>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers don't matter, but are read so the client isn't cut off.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next();
+    let path = parts.next().and_then(|target| target.split('?').next());
+    let (status, body) = match (method, path) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", lock(metrics).render()),
+        (Some("GET"), _) => ("404 Not Found", "not found, see /metrics\n".into()),
+        _ => ("405 Method Not Allowed", "only GET is allowed\n".into()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
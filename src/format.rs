@@ -0,0 +1,106 @@
+/// A format (or quality) a stream is available in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamFormat {
+    /// What the backend calls the format, e.g. `"720p60"` or `"95"`.
+    pub id: String,
+    /// The resolution of the video, e.g. `"1280x720"` or `"audio only"`.
+    pub resolution: Option<String>,
+    /// Anything else the backend tells about the format, e.g. `"best"`.
+    pub note: Option<String>,
+}
+
+impl StreamFormat {
+    /// Reads the formats of a stream from the table printed by
+    /// `youtube-dl -F` or `yt-dlp -F`.
+    ///
+    /// `youtube-dl` prints a "format code", "extension", "resolution" and
+    /// "note" column, while `yt-dlp` prints "ID", "EXT", "RESOLUTION" and
+    /// more, with the notes in "MORE INFO". Formats are read from the first
+    /// table only.
+    pub(crate) fn from_youtube_dl(stdout: &str) -> Vec<Self> {
+        let mut lines = stdout
+            .lines()
+            .skip_while(|line| !line.contains("Available formats for"))
+            .skip(1);
+        let header = match lines.next() {
+            Some(header) => header,
+            None => return vec![],
+        };
+        // Resolutions like "audio only" have spaces, so cells are read by
+        // the position of their column.
+        let (resolution, resolution_end, note) = if header.starts_with("format code") {
+            let note = column(header, "note");
+            (column(header, "resolution"), note, note)
+        } else {
+            let end = column(header, "FPS")
+                .or_else(|| column(header, "│"))
+                .or_else(|| column(header, "|"));
+            (
+                column(header, "RESOLUTION"),
+                end,
+                column(header, "MORE INFO"),
+            )
+        };
+        lines
+            .take_while(|line| !line.starts_with('['))
+            .filter(|line| !is_separator(line))
+            .filter_map(|line| {
+                let id = line.split_whitespace().next()?;
+                Some(Self {
+                    id: id.into(),
+                    resolution: resolution.and_then(|start| cell(line, start, resolution_end)),
+                    note: note.and_then(|start| cell(line, start, None)),
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the formats of a stream from the "Available streams" line
+    /// printed by `streamlink`, e.g. `audio_only, 720p (worst), 1080p (best)`.
+    pub(crate) fn from_streamlink(stdout: &str) -> Vec<Self> {
+        let streams = match stdout
+            .lines()
+            .find_map(|line| line.split("Available streams:").nth(1))
+        {
+            Some(streams) => streams,
+            None => return vec![],
+        };
+        streams
+            .split(',')
+            .map(str::trim)
+            .filter(|stream| !stream.is_empty())
+            .map(|stream| {
+                let mut parts = stream.splitn(2, " (");
+                let id = parts.next().unwrap_or_default();
+                let note = parts.next().map(|note| note.trim_end_matches(')').into());
+                Self {
+                    id: id.into(),
+                    resolution: None,
+                    note,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns where the column titled `title` starts in `header`, in chars.
+fn column(header: &str, title: &str) -> Option<usize> {
+    header
+        .find(title)
+        .map(|start| header[..start].chars().count())
+}
+
+/// Returns the trimmed text of `line` from char `start` up to `end`, if any.
+fn cell(line: &str, start: usize, end: Option<usize>) -> Option<String> {
+    let chars = line.chars().skip(start);
+    let cell: String = match end {
+        Some(end) => chars.take(end.saturating_sub(start)).collect(),
+        None => chars.collect(),
+    };
+    Some(cell.trim().to_string()).filter(|cell| !cell.is_empty())
+}
+
+/// Checks if `line` only separates the header of a table from its rows.
+fn is_separator(line: &str) -> bool {
+    line.trim().chars().all(|c| c == '─' || c == '-')
+}
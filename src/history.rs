@@ -0,0 +1,78 @@
+use dirs;
+use serde_json;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use cache::write_atomic;
+use errors::*;
+use {Stream, StreamStatus};
+
+/// How the status of a stream changed since it was last checked.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusChange {
+    WentOnline,
+    WentOffline,
+}
+
+/// The last known status of each stream, by canonical stream URL.
+///
+/// Tells which streams went online or offline since they were last checked.
+/// Unknown statuses are not recorded, so a failed check doesn't count as a
+/// change, and neither does the first check of a stream.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusHistory {
+    statuses: HashMap<String, StreamStatus>,
+}
+
+impl StatusHistory {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the history saved in `path`. A missing file makes for an empty
+    /// history.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).chain_err(|| "unable to parse status history"),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes the history to `path`, replacing it as a whole.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json =
+            serde_json::to_string(self).chain_err(|| "unable to serialize status history")?;
+        write_atomic(path.as_ref(), &json)
+    }
+
+    /// Returns the default location of the history, next to the
+    /// [`StatusCache`](struct.StatusCache.html).
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("streamlink-rs/history.json"))
+    }
+
+    /// Returns the last known status of `stream`.
+    pub fn last(&self, stream: &Stream) -> Option<StreamStatus> {
+        self.statuses.get(stream.canonical_url().as_str()).cloned()
+    }
+
+    /// Records that `stream` was found to be `status`, returning how that
+    /// changed since it was last recorded.
+    pub fn record(&mut self, stream: &Stream, status: StreamStatus) -> Option<StatusChange> {
+        if status == StreamStatus::Unknown {
+            return None;
+        }
+        let last = self
+            .statuses
+            .insert(stream.canonical_url().as_str().into(), status);
+        match (last, status) {
+            (Some(StreamStatus::Offline), StreamStatus::Online) => Some(StatusChange::WentOnline),
+            (Some(StreamStatus::Online), StreamStatus::Offline) => Some(StatusChange::WentOffline),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use ureq::{Agent, AgentBuilder, Proxy};
+
+use errors::*;
+
+/// Returns an HTTP client connecting through `proxy`, if any.
+pub fn agent(proxy: Option<&str>) -> Result<Agent> {
+    let mut builder = AgentBuilder::new();
+    if let Some(proxy) = proxy {
+        let proxy = Proxy::new(proxy).chain_err(|| ErrorKind::InvalidProxy(proxy.into()))?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build())
+}
@@ -0,0 +1,86 @@
+use serde_json::Value;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use StreamStatus;
+
+/// What is known about a stream besides its status.
+///
+/// Backends and platforms differ in what they report, so any of the details
+/// may be missing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamInfo {
+    /// Whether the stream is online.
+    pub status: StreamStatus,
+    /// The title of the stream (or of the video).
+    pub title: Option<String>,
+    /// How many people are watching right now, if told.
+    pub viewers: Option<u64>,
+    /// When the stream started.
+    pub started_at: Option<SystemTime>,
+    /// The game (or category) being streamed.
+    pub game: Option<String>,
+    /// Why the check failed, as told by the backend.
+    pub reason: Option<String>,
+}
+
+impl From<StreamStatus> for StreamInfo {
+    fn from(status: StreamStatus) -> Self {
+        Self {
+            status,
+            title: None,
+            viewers: None,
+            started_at: None,
+            game: None,
+            reason: None,
+        }
+    }
+}
+
+impl StreamInfo {
+    /// Reads the details of a stream from the output of `youtube-dl -j`.
+    ///
+    /// Falls back to just `status` if `json` can't be parsed.
+    pub(crate) fn from_youtube_dl(status: StreamStatus, json: &str) -> Self {
+        let json: Value = match ::serde_json::from_str(json) {
+            Ok(json) => json,
+            Err(_) => return status.into(),
+        };
+        Self {
+            status,
+            title: string(&json, &["fulltitle", "title"]),
+            // Not `view_count`, which is how many have ever watched it.
+            viewers: json["concurrent_view_count"].as_u64(),
+            started_at: json["release_timestamp"]
+                .as_u64()
+                .or_else(|| json["timestamp"].as_u64())
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+            game: string(&json, &["game"])
+                .or_else(|| json["categories"][0].as_str().map(String::from)),
+            reason: None,
+        }
+    }
+
+    /// Reads the details of a stream from the output of `streamlink --json`.
+    ///
+    /// Falls back to just `status` if `json` can't be parsed.
+    pub(crate) fn from_streamlink(status: StreamStatus, json: &str) -> Self {
+        let json: Value = match ::serde_json::from_str(json) {
+            Ok(json) => json,
+            Err(_) => return status.into(),
+        };
+        Self {
+            title: string(&json["metadata"], &["title"]),
+            game: string(&json["metadata"], &["category"]),
+            ..status.into()
+        }
+    }
+}
+
+/// Returns the first of the non-empty string `fields` of `json`.
+fn string(json: &Value, fields: &[&str]) -> Option<String> {
+    fields
+        .iter()
+        .filter_map(|field| json[field].as_str())
+        .find(|value| !value.is_empty())
+        .map(String::from)
+}
@@ -1,35 +1,796 @@
 #[macro_use]
 extern crate clap;
-extern crate dirs;
 extern crate streamlink;
 
-use clap::{App, Arg, SubCommand};
+use clap::{App, AppSettings, Arg, Shell, SubCommand};
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
-use streamlink::run;
+use std::time::Duration;
+use streamlink::{
+    check_urls_to, doctor, export_streams, import_streams_from, list, load_config_with_warnings,
+    open, poll, record, run_with, show_config, show_url, stream_names, watch, CheckOptions,
+    ColorChoice, Config, ErrorKind, GroupBy, ListFormat, Opener, OutputFormat, RunOptions,
+    SortOrder, Stream, StreamConfig, StreamStatus, ERROR_EXIT_CODE,
+};
 
-fn main() {
-    let matches = App::new("strs")
+/// Serves the statuses of the streams as metrics on `listen`, or the default
+/// address, see [`streamlink::export`].
+#[cfg(feature = "exporter")]
+fn export<P: AsRef<Path>>(
+    config_paths: &[P],
+    options: &RunOptions,
+    listen: Option<&str>,
+) -> streamlink::Result<()> {
+    streamlink::export(
+        config_paths,
+        options,
+        listen.unwrap_or(streamlink::DEFAULT_LISTEN),
+    )
+}
+
+/// There's no `--exporter` without the exporter feature.
+#[cfg(not(feature = "exporter"))]
+fn export<P: AsRef<Path>>(_: &[P], _: &RunOptions, _: Option<&str>) -> streamlink::Result<()> {
+    unreachable!("--exporter is only known with the exporter feature")
+}
+
+/// Tells what's odd about the configs in `config_paths` on stderr. Configs
+/// that can't be read are left to be told about by what reads them next.
+fn print_config_warnings(config_paths: &[PathBuf]) {
+    if let Ok((_, warnings)) = load_config_with_warnings(config_paths) {
+        for warning in warnings {
+            eprintln!("warning: {}", warning);
+        }
+    }
+}
+
+/// The names of the streams are completed by calling back into `strs`, as
+/// clap only completes what it knows of, and they're in the config.
+const NAMES_COMMAND: &str = "strs __complete-names 2>/dev/null";
+
+fn app() -> App<'static, 'static> {
+    let app = App::new("strs")
         .about("streamlink interface")
         .version(crate_version!())
-        .subcommand(SubCommand::with_name("list").about("list streamers"))
-        .subcommand(SubCommand::with_name("url").about("print formatted URL"))
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("list streamers")
+                .arg(
+                    Arg::with_name("pattern")
+                        .value_name("PATTERN")
+                        .help("only list the streams whose name matches PATTERN, e.g. 'nl*'"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("url")
+                .about("print the URL of the stream called NAME, or of every stream")
+                .arg(Arg::with_name("name").value_name("NAME")),
+        )
+        .subcommand(SubCommand::with_name("init").about("create a config to start out with"))
+        .subcommand(
+            SubCommand::with_name("add")
+                .about("add a stream to the config")
+                .arg(Arg::with_name("url").value_name("URL").required(true))
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .value_name("ALIAS")
+                        .help("what to call the stream instead of the name in its URL"),
+                )
+                .arg(
+                    Arg::with_name("tag")
+                        .long("tag")
+                        .value_name("TAG")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("a group to put the stream in"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("remove")
+                .about("remove a stream from the config")
+                .arg(
+                    Arg::with_name("stream")
+                        .value_name("NAME_OR_URL")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("watch")
+                .about("watch a stream in the config with its player")
+                .arg(Arg::with_name("name").value_name("NAME").required(true))
+                .arg(
+                    Arg::with_name("quality")
+                        .long("quality")
+                        .value_name("QUALITY")
+                        .help("the quality to watch the stream in, instead of the configured one"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .help("watch the stream without checking it's online first"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("record")
+                .about("record a stream in the config with its backend, until it ends")
+                .arg(Arg::with_name("name").value_name("NAME").required(true))
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("DIR")
+                        .help("the directory to record into, instead of the configured one"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .help("record the stream without checking it's online first"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("check streams not in the config, exiting with 1 if any is offline, or 2 if any can't be checked")
+                .arg(
+                    Arg::with_name("url")
+                        .value_name("URL")
+                        .required(true)
+                        .multiple(true)
+                        .help("the URLs to check, or - to read them from stdin, separated by whitespace, skipping lines starting with #"),
+                )
+                .arg(
+                    Arg::with_name("open")
+                        .long("open")
+                        .help("open the streams that are online in the browser"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("open")
+                .about("open streams in the config in the browser")
+                .arg(
+                    Arg::with_name("name")
+                        .value_name("NAME")
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("online")
+                        .long("online")
+                        .help("check the streams first, and open only the ones that are online"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("completions")
+                .about("print the completion script for SHELL")
+                .arg(
+                    Arg::with_name("shell")
+                        .value_name("SHELL")
+                        .required(true)
+                        .possible_values(&["bash", "zsh", "fish"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("manage the config")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("migrate")
+                        .about("upgrade the config to the current version, keeping a backup"),
+                )
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("print the config as read, and what it was read from"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("print the streams in the config, to share or back them up")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["opml", "json"])
+                        .value_name("FORMAT")
+                        .help("the format to write, instead of the one FILE ends in, or else JSON"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("the file to write to, instead of stdout"),
+                ),
+        )
+        .subcommand(import_subcommand())
+        .subcommand(SubCommand::with_name("doctor").about(
+            "check that the config, backend, player and directories are set up right",
+        ))
         .arg(
             Arg::with_name("config")
                 .short("c")
                 .long("config")
                 .takes_value(true)
-                .value_name("FILE"),
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("FILE")
+                .help("the config to use, or else $STREAMLINK_RS_CONFIG (may be given more than once, the later ones overriding the earlier ones)"),
+        )
+        .arg(
+            Arg::with_name("no-cache")
+                .long("no-cache")
+                .help("check every stream, even if its status is cached"),
+        )
+        .arg(
+            Arg::with_name("color")
+                .long("color")
+                .takes_value(true)
+                .possible_values(&["auto", "always", "never"])
+                .value_name("WHEN")
+                .help("when to color the output, instead of as set in the config"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .possible_values(&["text", "json", "table", "csv", "tsv"])
+                .value_name("FORMAT")
+                .help("how to print the statuses"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .conflicts_with("output")
+                .help("print the statuses as JSON, same as --output json"),
+        )
+        .arg(
+            Arg::with_name("table")
+                .long("table")
+                .conflicts_with_all(&["output", "json", "format"])
+                .help("print the statuses as a table of names, platforms, statuses and titles, same as --output table"),
+        )
+        .arg(
+            Arg::with_name("online")
+                .long("online")
+                .conflicts_with("offline")
+                .help("only print the streams that are online"),
+        )
+        .arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .help("only print the streams that are offline"),
+        )
+        .arg(
+            Arg::with_name("sort")
+                .long("sort")
+                .takes_value(true)
+                .possible_values(&["name", "status", "platform", "config"])
+                .value_name("ORDER")
+                .help("the order to print the statuses in, instead of as set in the config; sorted statuses are printed once all are checked, rather than as each is"),
+        )
+        .arg(
+            Arg::with_name("group-by")
+                .long("group-by")
+                .takes_value(true)
+                .possible_values(&["none", "platform"])
+                .value_name("KEY")
+                .help("what to group the statuses under headers by, instead of as set in the config"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .value_name("TEMPLATE")
+                .help("the line to print each status as, e.g. '{name}:{status}', with {name}, {url}, {platform}, {status}, {title}, {mark}, {cached_age} and {checked_at} filled in"),
+        )
+        .arg(
+            Arg::with_name("no-progress")
+                .long("no-progress")
+                .help("don't show a progress bar while checking the streams; there's none when stdout isn't a terminal"),
+        )
+        .arg(
+            Arg::with_name("no-summary")
+                .long("no-summary")
+                .help("don't print how many streams are online, offline and unknown after the statuses"),
+        )
+        .arg(
+            Arg::with_name("poll")
+                .long("watch")
+                .help("check the streams over and over, until interrupted"),
+        )
+        .arg(
+            Arg::with_name("interval")
+                .long("interval")
+                .takes_value(true)
+                .value_name("SECS")
+                .requires("poll")
+                .help("how many seconds to wait between checks with --watch, instead of as set in the config"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("print nothing; exit with 0 if any stream is online, 1 if all are offline, or 2 if they can't be checked"),
+        )
+        .arg(
+            Arg::with_name("tag")
+                .short("t")
+                .long("tag")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("TAG")
+                .help("only check streams tagged TAG (may be given more than once)"),
+        )
+        .arg(
+            Arg::with_name("pattern")
+                .value_name("PATTERN")
+                .help("only check the streams whose name matches PATTERN, e.g. 'nl*' ('*' and '?' stand for any characters)"),
+        );
+    #[cfg(feature = "exporter")]
+    let app = app
+        .arg(
+            Arg::with_name("exporter")
+                .long("exporter")
+                .help("check the streams over and over, serving their statuses as Prometheus metrics at /metrics"),
+        )
+        .arg(
+            Arg::with_name("listen")
+                .long("listen")
+                .takes_value(true)
+                .value_name("ADDR")
+                .requires("exporter")
+                .help("the address to serve the metrics on with --exporter, instead of 127.0.0.1:9184"),
+        );
+    app
+}
+
+/// The `import` subcommand, importing from Twitch only with the twitch-api
+/// feature.
+fn import_subcommand() -> App<'static, 'static> {
+    let import = SubCommand::with_name("import")
+        .about("add streams from elsewhere to the config")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("file")
+                .about("add the streams in a list written by `strs export`, in either format")
+                .arg(Arg::with_name("path").value_name("PATH").required(true)),
+        );
+    #[cfg(feature = "twitch-api")]
+    let import = import.subcommand(
+        SubCommand::with_name("twitch")
+            .about("add the channels you follow on Twitch, authorizing the app set in [twitch]")
+            .arg(
+                Arg::with_name("user")
+                    .long("user")
+                    .takes_value(true)
+                    .value_name("LOGIN")
+                    .help("make sure the follows are those of LOGIN"),
+            ),
+    );
+    import
+}
+
+/// Writes the completion script of `strs` for `shell` to `out`, completing
+/// the names of the streams for `watch`, `record`, `open`, `url`
+/// and `remove`.
+fn completions<W: Write>(shell: Shell, out: &mut W) -> io::Result<()> {
+    let mut script = vec![];
+    app().gen_completions_to("strs", shell, &mut script);
+    let mut script = String::from_utf8(script).expect("completion script isn't UTF-8");
+    match shell {
+        Shell::Bash => {
+            script.push_str(&format!(
+                r#"
+_strs_names() {{
+    case "${{COMP_WORDS[COMP_CWORD-1]}}" in
+        watch|record|open|url|remove)
+            if [[ "${{COMP_WORDS[COMP_CWORD]}}" != -* ]]; then
+                COMPREPLY=( $(compgen -W "$({})" -- "${{COMP_WORDS[COMP_CWORD]}}") )
+                return 0
+            fi
+            ;;
+    esac
+    _strs "$@"
+}}
+
+complete -F _strs_names -o bashdefault -o default strs
+"#,
+                NAMES_COMMAND
+            ));
+        }
+        Shell::Zsh => {
+            // Call the names completion instead of the generated one, now
+            // and from then on.
+            let generated = script.trim_end().trim_end_matches("_strs \"$@\"").len();
+            script.truncate(generated);
+            script.push_str(&format!(
+                r#"_strs_names() {{
+    case "${{words[CURRENT-1]}}" in
+        watch|record|open|url|remove) compadd -- ${{(f)"$({})"}} ;;
+        *) _strs "$@" ;;
+    esac
+}}
+
+compdef _strs_names strs
+_strs_names "$@"
+"#,
+                NAMES_COMMAND
+            ));
+        }
+        Shell::Fish => {
+            script.push_str(&format!(
+                "complete -c strs -n \"__fish_seen_subcommand_from watch record open url remove\" -f -a \"({})\"\n",
+                NAMES_COMMAND
+            ));
+        }
+        _ => {}
+    }
+    out.write_all(script.as_bytes())
+}
+
+/// Reads the URLs in `reader`, separated by whitespace, along with the lines
+/// they're on, counted from 1. Lines starting with `#` are skipped.
+fn read_urls<R: BufRead>(reader: R) -> io::Result<Vec<(usize, String)>> {
+    let mut urls = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        urls.extend(line.split_whitespace().map(|url| (i + 1, url.to_string())));
+    }
+    Ok(urls)
+}
+
+fn main() {
+    // Left out of the completions, as clap can't complete subcommands
+    // named with `__`.
+    let matches = app()
+        .subcommand(
+            SubCommand::with_name("__complete-names")
+                .setting(AppSettings::Hidden)
+                .about("print the names of the streams in the config, one per line"),
         )
         .get_matches();
+    if let Some(args) = matches.subcommand_matches("completions") {
+        let shell = args.value_of("shell").unwrap().parse().unwrap();
+        if let Err(e) = completions(shell, &mut io::stdout()) {
+            println!("error: {}", e);
+            ::std::process::exit(1);
+        }
+        return;
+    }
 
-    let default_config_path = dirs::config_dir()
-        .unwrap_or(PathBuf::new().join("/"))
-        .join("streamlink-rs/config.toml");
-    let config_path: &Path = match matches.value_of("config") {
-        Some(path) => Path::new(path),
-        None => default_config_path.as_path(),
+    // Configs given more than once are merged, the later ones winning.
+    let mut path_variable = None;
+    let config_paths: Vec<PathBuf> = match matches.values_of("config") {
+        Some(paths) => paths.map(PathBuf::from).collect(),
+        None => match Config::resolve_path_and_variable(None) {
+            Some((path, variable)) => {
+                path_variable = variable;
+                vec![path]
+            }
+            None => {
+                println!("error: no config directory found, pass a config with --config");
+                ::std::process::exit(1);
+            }
+        },
+    };
+    if matches.subcommand_matches("__complete-names").is_some() {
+        // Completing is best effort, so nothing is said if it fails.
+        if let Ok(names) = stream_names(&config_paths) {
+            for name in names {
+                println!("{}", name);
+            }
+        }
+        return;
+    }
+    if matches.subcommand_matches("init").is_some() {
+        let config_path = &config_paths[0];
+        match Config::init_at(config_path) {
+            Ok(()) => println!("created config at '{}'", config_path.display()),
+            Err(e) => {
+                println!("error: {}", e);
+                ::std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(config) = matches.subcommand_matches("config") {
+        if config.subcommand_matches("migrate").is_some() {
+            for config_path in &config_paths {
+                match Config::migrate_file(config_path) {
+                    Ok(true) => println!("migrated config at '{}'", config_path.display()),
+                    Ok(false) => println!("config at '{}' is up to date", config_path.display()),
+                    Err(e) => {
+                        println!("error: {}", e);
+                        ::std::process::exit(1);
+                    }
+                }
+            }
+        }
+        if config.subcommand_matches("show").is_some() {
+            print_config_warnings(&config_paths);
+            if let Err(e) = show_config(&config_paths, path_variable) {
+                println!("error: {}", e);
+                ::std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if matches.subcommand_matches("doctor").is_some() {
+        let color = match matches.value_of("color") {
+            Some("always") => ColorChoice::Always,
+            Some("never") => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        };
+        match doctor(&config_paths, color.enabled()) {
+            Ok(true) => return,
+            Ok(false) => ::std::process::exit(1),
+            Err(e) => {
+                println!("error: {}", e);
+                ::std::process::exit(1);
+            }
+        }
+    }
+    if let Some(check) = matches.subcommand_matches("check") {
+        let mut urls = vec![];
+        // URLs read from stdin that aren't streams are told by their line,
+        // and don't keep the others from being checked.
+        let mut invalid = false;
+        for url in check.values_of("url").unwrap() {
+            if url != "-" {
+                urls.push(url.to_string());
+                continue;
+            }
+            let stdin = io::stdin();
+            let read = read_urls(stdin.lock()).unwrap_or_else(|e| {
+                println!("error: unable to read URLs: {}", e);
+                ::std::process::exit(2);
+            });
+            for (line, url) in read {
+                match Stream::from_string(url.clone()) {
+                    Ok(_) => urls.push(url),
+                    Err(e) => {
+                        println!("error: line {}: {}", line, e);
+                        invalid = true;
+                    }
+                }
+            }
+        }
+        let color = match matches.value_of("color") {
+            Some("always") => ColorChoice::Always,
+            Some("never") => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        };
+        let stdout = io::stdout();
+        let opener = Opener::default();
+        match check_urls_to(
+            &urls,
+            &CheckOptions::default(),
+            color.enabled(),
+            if check.is_present("open") {
+                Some(&opener)
+            } else {
+                None
+            },
+            &mut stdout.lock(),
+        ) {
+            Ok(_) if invalid => ::std::process::exit(2),
+            Ok(code) => ::std::process::exit(code),
+            Err(e) => {
+                println!("error: {}", e);
+                ::std::process::exit(2);
+            }
+        }
+    }
+    // Checking the streams exits as told by `RunSummary::exit_code`, and
+    // with `ERROR_EXIT_CODE` if they can't be checked.
+    let checking = matches.subcommand_name().is_none();
+    let quiet = checking && matches.is_present("quiet");
+    let error_code = if checking { ERROR_EXIT_CODE } else { 1 };
+    for config_path in &config_paths {
+        if !config_path.is_file() {
+            if !quiet {
+                println!(
+                    "error: no config found at '{}', create one with `strs init`",
+                    config_path.display()
+                );
+            }
+            ::std::process::exit(error_code);
+        }
+    }
+    let edited = match matches.subcommand() {
+        ("add", Some(add)) => {
+            let mut stream = StreamConfig::new(add.value_of("url").unwrap().into());
+            stream.name = add.value_of("name").map(String::from);
+            stream.tags = add
+                .values_of("tag")
+                .map(|tags| tags.map(String::from).collect())
+                .unwrap_or_default();
+            let added = format!(
+                "added '{}' to config at '{}'",
+                stream.url,
+                config_paths[0].display()
+            );
+            Some(Config::add_stream_to_file(&config_paths[0], stream).map(|()| added))
+        }
+        ("remove", Some(remove)) => Some(
+            Config::remove_stream_from_file(&config_paths[0], remove.value_of("stream").unwrap())
+                .map(|stream| {
+                    format!(
+                        "removed '{}' from config at '{}'",
+                        stream.name.unwrap_or(stream.url),
+                        config_paths[0].display()
+                    )
+                }),
+        ),
+        ("import", Some(import)) => {
+            let imported = match import.subcommand() {
+                ("file", Some(file)) => {
+                    import_streams_from(&config_paths[0], file.value_of("path").unwrap())
+                }
+                #[cfg(feature = "twitch-api")]
+                ("twitch", Some(twitch)) => streamlink::import_twitch_follows(
+                    &config_paths[0],
+                    twitch.value_of("user"),
+                    |code| {
+                        println!(
+                            "to import your follows, enter {} at {}",
+                            code.user_code, code.verification_uri
+                        )
+                    },
+                ),
+                _ => unreachable!("import requires a subcommand"),
+            };
+            Some(imported.map(|summary| format!("{} at '{}'", summary, config_paths[0].display())))
+        }
+        _ => None,
+    };
+    if let Some(edited) = edited {
+        match edited {
+            Ok(message) => println!("{}", message),
+            Err(e) => {
+                println!("error: {}", e);
+                for e in e.iter().skip(1) {
+                    println!("caused by: {}", e);
+                }
+                ::std::process::exit(match *e.kind() {
+                    ErrorKind::DuplicateStream(..) => 3,
+                    ErrorKind::UnknownStream(..) | ErrorKind::AmbiguousStream(..) => 4,
+                    ErrorKind::ConfigWrite(..) => 5,
+                    _ => 1,
+                });
+            }
+        }
+        return;
+    }
+    let options = RunOptions {
+        no_cache: matches.is_present("no-cache"),
+        tags: matches
+            .values_of("tag")
+            .map(|tags| tags.map(String::from).collect())
+            .unwrap_or_default(),
+        pattern: matches
+            .subcommand_matches("list")
+            .unwrap_or(&matches)
+            .value_of("pattern")
+            .map(String::from),
+        color: matches.value_of("color").map(|color| match color {
+            "always" => ColorChoice::Always,
+            "never" => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        }),
+        format: match matches.value_of("output") {
+            _ if matches.is_present("json") => OutputFormat::Json,
+            _ if matches.is_present("table") => OutputFormat::Table,
+            Some("json") => OutputFormat::Json,
+            Some("table") => OutputFormat::Table,
+            Some("csv") => OutputFormat::Csv,
+            Some("tsv") => OutputFormat::Tsv,
+            _ => OutputFormat::Text,
+        },
+        only: if matches.is_present("online") {
+            Some(StreamStatus::Online)
+        } else if matches.is_present("offline") {
+            Some(StreamStatus::Offline)
+        } else {
+            None
+        },
+        sort: matches.value_of("sort").map(|sort| match sort {
+            "name" => SortOrder::Name,
+            "status" => SortOrder::Status,
+            "platform" => SortOrder::Platform,
+            _ => SortOrder::Config,
+        }),
+        group_by: matches.value_of("group-by").map(|key| match key {
+            "platform" => GroupBy::Platform,
+            _ => GroupBy::None,
+        }),
+        template: matches.value_of("format").map(String::from),
+        poll_interval: match matches.value_of("interval").map(str::parse) {
+            Some(Ok(secs)) if secs > 0 => Some(Duration::from_secs(secs)),
+            Some(_) => {
+                println!("error: --interval takes a number of seconds, more than 0");
+                ::std::process::exit(error_code);
+            }
+            None => None,
+        },
+        quiet,
+        no_summary: matches.is_present("no-summary"),
+        no_progress: matches.is_present("no-progress"),
     };
-    if let Err(ref e) = run(config_path) {
+    if !quiet {
+        print_config_warnings(&config_paths);
+    }
+    let result = if matches.subcommand_matches("list").is_some() {
+        list(&config_paths, &options)
+    } else if let Some(url) = matches.subcommand_matches("url") {
+        show_url(&config_paths, &options, url.value_of("name"))
+    } else if let Some(args) = matches.subcommand_matches("watch") {
+        watch(
+            &config_paths,
+            args.value_of("name").unwrap(),
+            args.value_of("quality"),
+            args.is_present("force"),
+        )
+        .map(|status| {
+            // Exit as the player did.
+            if !status.success() {
+                ::std::process::exit(status.code().unwrap_or(1));
+            }
+        })
+    } else if let Some(args) = matches.subcommand_matches("export") {
+        let output = args.value_of("output").map(Path::new);
+        let format = match args.value_of("format") {
+            Some("opml") => ListFormat::Opml,
+            Some(_) => ListFormat::Json,
+            None => output
+                .and_then(ListFormat::from_path)
+                .unwrap_or(ListFormat::Json),
+        };
+        export_streams(&config_paths, format, output)
+    } else if let Some(args) = matches.subcommand_matches("open") {
+        let names: Vec<&str> = args.values_of("name").unwrap().collect();
+        open(&config_paths, &names, args.is_present("online"))
+    } else if let Some(args) = matches.subcommand_matches("record") {
+        record(
+            &config_paths,
+            args.value_of("name").unwrap(),
+            args.value_of("output").map(Path::new),
+            args.is_present("force"),
+        )
+        .and_then(|recording| {
+            println!("recording to '{}'", recording.path().display());
+            recording.wait()
+        })
+        .map(|status| {
+            // Exit as the backend did.
+            if !status.success() {
+                ::std::process::exit(status.code().unwrap_or(1));
+            }
+        })
+    } else if matches.is_present("poll") {
+        poll(&config_paths, &options)
+    } else if matches.is_present("exporter") {
+        export(&config_paths, &options, matches.value_of("listen"))
+    } else {
+        run_with(&config_paths, &options).map(|summary| ::std::process::exit(summary.exit_code()))
+    };
+    if let Err(ref e) = result {
+        if quiet {
+            ::std::process::exit(error_code);
+        }
+
+        // Mistakes in the config are the user's, not a crash, so they're
+        // told without causes or a backtrace.
+        if let ErrorKind::ConfigParse(..) = *e.kind() {
+            println!("{}", e);
+            ::std::process::exit(error_code);
+        }
+
         println!("error: {}", e);
 
         for e in e.iter().skip(1) {
@@ -42,6 +803,40 @@ fn main() {
             println!("backtrace: {:?}", backtrace);
         }
 
-        ::std::process::exit(1);
+        ::std::process::exit(error_code);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Shell;
+
+    #[test]
+    fn completions() {
+        for &shell in &[Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let mut script = vec![];
+            super::completions(shell, &mut script).unwrap();
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("watch"), "{}", script);
+            assert!(script.contains(super::NAMES_COMMAND), "{}", script);
+        }
+    }
+
+    #[test]
+    fn read_urls() {
+        let input = "# streams to check\n\
+                     https://twitch.tv/gogcom\n\
+                     \n\
+                     \thttps://kick.com/xqc   not-a-url \n\
+                     \x20 # https://twitch.tv/commented\n";
+        assert_eq!(
+            vec![
+                (2, "https://twitch.tv/gogcom".to_string()),
+                (4, "https://kick.com/xqc".to_string()),
+                (4, "not-a-url".to_string()),
+            ],
+            super::read_urls(input.as_bytes()).unwrap()
+        );
+        assert!(super::read_urls(&b""[..]).unwrap().is_empty());
     }
 }
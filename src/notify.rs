@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use errors::*;
+use {Config, StatusReport, Stream, StreamStatus};
+
+/// Tells the user about streams going online (or offline), e.g. with a
+/// desktop notification.
+pub trait Notifier {
+    /// Tells the user about the stream in `report`, which just went online
+    /// or offline.
+    fn notify(&self, report: &StatusReport) -> Result<()>;
+}
+
+/// Shows desktop notifications, with the `notifications` feature.
+#[cfg(feature = "notifications")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DesktopNotifier;
+
+#[cfg(feature = "notifications")]
+impl Notifier for DesktopNotifier {
+    fn notify(&self, report: &StatusReport) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("streamlink-rs")
+            .summary(&summary(report))
+            .body(&body(report))
+            .show()
+            .map(|_| ())
+            .map_err(|e| ErrorKind::NotificationFailed(e.to_string()).into())
+    }
+}
+
+/// Returns the notifier to use by default: a [`DesktopNotifier`] with the
+/// `notifications` feature, or else none.
+///
+/// [`DesktopNotifier`]: struct.DesktopNotifier.html
+pub(crate) fn default_notifier() -> Option<Box<dyn Notifier>> {
+    #[cfg(feature = "notifications")]
+    {
+        Some(Box::new(DesktopNotifier))
+    }
+    #[cfg(not(feature = "notifications"))]
+    {
+        None
+    }
+}
+
+/// Returns what a notification about `report` is headed with, e.g.
+/// `gogcom is online`.
+#[cfg(feature = "notifications")]
+fn summary(report: &StatusReport) -> String {
+    format!("{} is {}", report.name, report.status)
+}
+
+/// Returns the text of a notification about `report`: the title of the
+/// stream, if known, over its URL.
+#[cfg(feature = "notifications")]
+fn body(report: &StatusReport) -> String {
+    match report.title {
+        Some(ref title) => format!("{}\n{}", title, report.url),
+        None => report.url.clone(),
+    }
+}
+
+/// Which status changes to notify of, as set in a config.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NotifyRules {
+    /// The canonical URLs of the streams to notify of.
+    streams: HashSet<String>,
+    /// Whether to notify of streams going offline too.
+    offline: bool,
+}
+
+impl NotifyRules {
+    /// The rules set in `config` for `streams`.
+    pub(crate) fn new<'a, I>(config: &Config, streams: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Stream>,
+    {
+        NotifyRules {
+            streams: streams
+                .into_iter()
+                .filter(|stream| config.notify_for(stream))
+                .map(|stream| stream.canonical_url().to_string())
+                .collect(),
+            offline: config.notify_offline,
+        }
+    }
+
+    /// Returns whether to notify of `report`.
+    fn wants(&self, report: &StatusReport) -> bool {
+        let status = match report.status {
+            StreamStatus::Online => true,
+            StreamStatus::Offline => self.offline,
+            StreamStatus::Unknown => false,
+        };
+        report.changed && status && self.streams.contains(&report.url)
+    }
+}
+
+/// Tells `notifier` about the streams in `reports` that went online or
+/// offline, as wanted by `rules`. Notifications that fail are told to `err`,
+/// and don't keep the others from being sent.
+pub(crate) fn notify_changes<E: Write>(
+    notifier: &dyn Notifier,
+    rules: &NotifyRules,
+    reports: &[StatusReport],
+    err: &mut E,
+) {
+    for report in reports.iter().filter(|report| rules.wants(report)) {
+        if let Err(e) = notifier.notify(report) {
+            let _ = writeln!(err, "warning: unable to notify of {}: {}", report.name, e);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fmt;
+use url::percent_encoding::percent_decode;
+use url::{idna, Host, Url};
+
+use config::PlatformConfig;
+use PlatformKind;
+
+/// A streaming platform that streamlink knows how to handle.
+///
+/// Implement this to teach streamlink about platforms it doesn't support
+/// out of the box, and add them with [`PlatformRegistry::register`].
+pub trait Platform {
+    /// Returns a unique identifier of the platform, e.g. `"owncast"`.
+    fn id(&self) -> &str;
+
+    /// Checks if the platform serves streams on `host`.
+    fn matches_host(&self, host: &str) -> bool;
+
+    /// Returns the name (aka ID) of the stream on `url`.
+    ///
+    /// Note that the path of `url` is percent-encoded.
+    fn extract_name(&self, url: &Url) -> Option<String>;
+}
+
+/// An ordered collection of platforms used to classify stream URLs.
+///
+/// Platforms are tried in registration order, so built-in platforms take
+/// precedence over registered ones.
+pub struct PlatformRegistry {
+    platforms: Vec<(PlatformKind, Box<dyn Platform>)>,
+}
+
+impl PlatformRegistry {
+    /// Creates a registry with all built-in platforms.
+    pub fn new() -> Self {
+        let mut kinds: Vec<PlatformKind> = vec![];
+        for &(_, kind) in HOSTS {
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        let platforms = kinds
+            .into_iter()
+            .map(|kind| {
+                let platform: Box<dyn Platform> = Box::new(Builtin(kind));
+                (kind, platform)
+            })
+            .collect();
+        Self { platforms }
+    }
+
+    /// Adds `platform` to the registry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate streamlink;
+    /// extern crate url;
+    ///
+    /// use streamlink::{Platform, PlatformRegistry, Stream};
+    /// use url::Url;
+    ///
+    /// struct Owncast;
+    ///
+    /// impl Platform for Owncast {
+    ///     fn id(&self) -> &str {
+    ///         "owncast"
+    ///     }
+    ///
+    ///     fn matches_host(&self, host: &str) -> bool {
+    ///         host == "live.example.org"
+    ///     }
+    ///
+    ///     fn extract_name(&self, _url: &Url) -> Option<String> {
+    ///         Some("example".into())
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut registry = PlatformRegistry::new();
+    /// registry.register(Owncast);
+    ///
+    /// let url = Url::parse("https://live.example.org").unwrap();
+    /// let stream = Stream::from_url_with_registry(url, &registry).unwrap();
+    /// assert_eq!("example", stream.name().unwrap());
+    /// # }
+    /// ```
+    pub fn register<P: Platform + 'static>(&mut self, platform: P) {
+        self.platforms
+            .push((PlatformKind::Custom, Box::new(platform)));
+    }
+
+    /// Registers the custom platforms from [`Config::platforms`].
+    ///
+    /// [`Config::platforms`]: struct.Config.html#structfield.platforms
+    pub fn register_config(&mut self, platforms: &HashMap<String, PlatformConfig>) {
+        let mut platforms: Vec<_> = platforms.iter().collect();
+        // The most specific configured host wins.
+        platforms.sort_by_key(|&(domain, _)| std::cmp::Reverse(domain.len()));
+        for (domain, platform) in platforms {
+            self.register(Configured {
+                domain: domain.clone(),
+                name_segment: platform.name_segment,
+            });
+        }
+    }
+
+    /// Finds the platform serving `url`, along with its kind.
+    pub(crate) fn classify(&self, url: &Url) -> Option<(PlatformKind, &dyn Platform)> {
+        let host = match url.host() {
+            Some(Host::Domain(host)) => host,
+            _ => return None,
+        };
+        self.platforms
+            .iter()
+            .find(|(_, platform)| platform.matches_host(host))
+            .map(|&(kind, ref platform)| (kind, platform.as_ref()))
+    }
+}
+
+impl Default for PlatformRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for PlatformRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.platforms.iter().map(|(_, platform)| platform.id()))
+            .finish()
+    }
+}
+
+/// Known stream hosts. Subdomains of each host resolve to the same kind.
+const HOSTS: &[(&str, PlatformKind)] = &[
+    ("youtube.com", PlatformKind::Youtube),
+    ("youtu.be", PlatformKind::Youtube),
+    ("twitch.tv", PlatformKind::Twitch),
+    ("kick.com", PlatformKind::Kick),
+    ("dailymotion.com", PlatformKind::Dailymotion),
+    ("vimeo.com", PlatformKind::Vimeo),
+    ("facebook.com", PlatformKind::Facebook),
+    ("fb.gg", PlatformKind::Facebook),
+    ("trovo.live", PlatformKind::Trovo),
+    ("picarto.tv", PlatformKind::Picarto),
+    ("rumble.com", PlatformKind::Rumble),
+    ("dlive.tv", PlatformKind::Dlive),
+    ("live.bilibili.com", PlatformKind::Bilibili),
+    ("play.afreecatv.com", PlatformKind::Afreeca),
+    ("sooplive.co.kr", PlatformKind::Afreeca),
+    ("odysee.com", PlatformKind::Odysee),
+    ("goodgame.ru", PlatformKind::Goodgame),
+    ("vkplay.live", PlatformKind::Vkplay),
+    ("huya.com", PlatformKind::Huya),
+    ("douyu.com", PlatformKind::Douyu),
+    ("steamcommunity.com", PlatformKind::Steam),
+];
+
+/// Checks if `id` is the id of a known platform, e.g. `"twitch"`.
+pub(crate) fn is_known_platform(id: &str) -> bool {
+    HOSTS.iter().any(|&(_, kind)| kind.id() == id)
+}
+
+/// Checks if `host` is `domain` itself or any of its subdomains
+/// (e.g. `www.twitch.tv` or `m.twitch.tv` for `twitch.tv`).
+///
+/// Hosts are compared case-insensitively, and internationalized names match
+/// regardless of whether they are written in Unicode or punycode. Ports are
+/// not part of the host, so `twitch.tv:8080` matches `twitch.tv` as well.
+pub fn host_matches(host: &str, domain: &str) -> bool {
+    let host = normalize_host(host);
+    let domain = normalize_host(domain);
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// Converts `host` to its lowercase ASCII (punycode) form.
+fn normalize_host(host: &str) -> String {
+    idna::domain_to_ascii(host).unwrap_or_else(|_| host.to_lowercase())
+}
+
+/// Returns the percent-decoded path of `url`.
+fn decoded_path(url: &Url) -> String {
+    percent_decode(url.path().as_bytes())
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Returns the raw value of the non-empty query parameter `key`.
+pub fn query_param<'a>(url: &'a Url, key: &str) -> Option<&'a str> {
+    url.query().and_then(|query| {
+        query
+            .split('&')
+            .filter_map(|pair| {
+                let mut pair = pair.splitn(2, '=');
+                match (pair.next(), pair.next()) {
+                    (Some(k), Some(value)) if k == key => Some(value),
+                    _ => None,
+                }
+            })
+            .find(|value| !value.is_empty())
+    })
+}
+
+impl<'a> From<&'a Url> for PlatformKind {
+    fn from(url: &Url) -> Self {
+        match url.host() {
+            Some(Host::Domain(host)) => HOSTS
+                .iter()
+                .find(|(domain, _)| host_matches(host, domain))
+                .map_or(PlatformKind::Other, |&(_, kind)| kind),
+            _ => PlatformKind::Other,
+        }
+    }
+}
+
+/// A platform supported out of the box.
+struct Builtin(PlatformKind);
+
+impl Platform for Builtin {
+    fn id(&self) -> &str {
+        self.0.id()
+    }
+
+    fn matches_host(&self, host: &str) -> bool {
+        HOSTS
+            .iter()
+            .any(|(domain, kind)| *kind == self.0 && host_matches(host, domain))
+    }
+
+    fn extract_name(&self, url: &Url) -> Option<String> {
+        let path = decoded_path(url);
+        let mut path_parts = path.split('/').filter(|part| !part.is_empty());
+
+        let name = match self.0 {
+            PlatformKind::Twitch => match path_parts.next() {
+                Some("team") => path_parts.next(),
+                channel => channel,
+            },
+            PlatformKind::Kick
+            | PlatformKind::Facebook
+            | PlatformKind::Picarto
+            | PlatformKind::Dlive
+            | PlatformKind::Bilibili
+            | PlatformKind::Afreeca
+            | PlatformKind::Vkplay
+            | PlatformKind::Huya
+            | PlatformKind::Douyu => path_parts.next(),
+            PlatformKind::Youtube => match path_parts.next() {
+                Some("user") | Some("channel") | Some("c") => path_parts.next(),
+                Some("watch") => query_param(url, "v"),
+                Some(handle) if handle.starts_with('@') => {
+                    Some(&handle[1..]).filter(|handle| !handle.is_empty())
+                }
+                Some(id) => Some(id),
+                None => None,
+            },
+            PlatformKind::Dailymotion => match path_parts.next() {
+                Some("video") => path_parts.next(),
+                Some(channel) => Some(channel),
+                None => None,
+            },
+            PlatformKind::Vimeo => match path_parts.next() {
+                Some("event") => path_parts.next(),
+                Some(user) => Some(user),
+                None => None,
+            },
+            PlatformKind::Trovo => match path_parts.next() {
+                Some("s") => path_parts.next(),
+                Some(channel) => Some(channel),
+                None => None,
+            },
+            PlatformKind::Rumble => match path_parts.next() {
+                Some("c") | Some("user") => path_parts.next(),
+                Some(video) => Some(video.trim_end_matches(".html")),
+                None => None,
+            },
+            PlatformKind::Odysee => path_parts
+                .next()
+                .map(|channel| channel.trim_start_matches('@'))
+                .and_then(|channel| channel.split(':').next())
+                .filter(|channel| !channel.is_empty()),
+            PlatformKind::Goodgame => match path_parts.next() {
+                Some("channel") => path_parts.next(),
+                Some(channel) => Some(channel),
+                None => None,
+            },
+            PlatformKind::Steam => match (path_parts.next(), path_parts.next()) {
+                (Some("broadcast"), Some("watch")) => path_parts.next(),
+                _ => None,
+            },
+            PlatformKind::TwitchTeam | PlatformKind::Custom | PlatformKind::Other => None,
+        };
+        name.map(String::from)
+    }
+}
+
+/// A platform configured in [`Config::platforms`].
+///
+/// [`Config::platforms`]: ../struct.Config.html#structfield.platforms
+struct Configured {
+    domain: String,
+    name_segment: Option<usize>,
+}
+
+impl Platform for Configured {
+    fn id(&self) -> &str {
+        &self.domain
+    }
+
+    fn matches_host(&self, host: &str) -> bool {
+        host_matches(host, &self.domain)
+    }
+
+    fn extract_name(&self, url: &Url) -> Option<String> {
+        let path = decoded_path(url);
+        let name = match self.name_segment {
+            Some(index) => path.split('/').filter(|part| !part.is_empty()).nth(index),
+            None => Some(path.trim_matches('/')).filter(|path| !path.is_empty()),
+        };
+        name.map(String::from)
+    }
+}
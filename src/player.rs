@@ -0,0 +1,56 @@
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Command;
+
+use {Config, Stream};
+
+/// How to watch a stream with [`Stream::watch`](struct.Stream.html#method.watch).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerOptions {
+    /// The player, e.g. `"mpv"`, or `"streamlink"` to have it pick the
+    /// quality and open a player of its own.
+    pub player: String,
+    /// Extra arguments passed to the player, before the URL.
+    pub args: Vec<String>,
+    /// The quality to watch the stream in, e.g. `"best"` or `"720p"`. Only
+    /// `streamlink` is told it; other players pick one themselves.
+    pub quality: String,
+}
+
+impl PlayerOptions {
+    /// The options to watch `stream` with, as set in `config`.
+    pub fn for_stream(config: &Config, stream: &Stream) -> Self {
+        PlayerOptions {
+            player: config.player_for(stream).into(),
+            args: config.player_args.clone(),
+            quality: config.quality_for(stream).into(),
+        }
+    }
+
+    /// Returns the command watching the stream at `url`.
+    pub(crate) fn command(&self, url: &str) -> Command {
+        let mut command = Command::new(&self.player);
+        command.args(&self.args).arg(url);
+        if is_streamlink(&self.player) {
+            command.arg(&self.quality);
+        }
+        command
+    }
+}
+
+impl Default for PlayerOptions {
+    fn default() -> Self {
+        let config = Config::default();
+        PlayerOptions {
+            player: config.player,
+            args: config.player_args,
+            quality: config.quality,
+        }
+    }
+}
+
+/// Tells whether `player` is `streamlink`, which takes the quality after the
+/// URL.
+fn is_streamlink(player: &str) -> bool {
+    Path::new(player).file_stem() == Some(OsStr::new("streamlink"))
+}
@@ -0,0 +1,854 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use backend::CheckOptions;
+use cache::{CacheEntry, StatusCache};
+use errors::*;
+use info::StreamInfo;
+#[cfg(feature = "http")]
+use twitch;
+#[cfg(feature = "twitch-api")]
+use twitch::{Helix, HELIX_BATCH_SIZE};
+#[cfg(feature = "youtube-api")]
+use youtube::DataApi;
+use {CheckResult, PlatformKind, Stream, StreamStatus};
+
+/// Something that can check if a stream is online.
+///
+/// Implement this to check streams without running a backend program,
+/// e.g. through a platform API, or with canned results in tests.
+///
+/// Providers are shared by the threads of
+/// [`Streamlink::status_parallel`](struct.Streamlink.html#method.status_parallel),
+/// so they must be `Sync`.
+///
+/// # Examples
+///
+/// ```rust
+/// use streamlink::{Result, Stream, StatusProvider, StreamStatus, Streamlink};
+///
+/// struct AlwaysOnline;
+///
+/// impl StatusProvider for AlwaysOnline {
+///     fn check(&self, _stream: &Stream) -> Result<StreamStatus> {
+///         Ok(StreamStatus::Online)
+///     }
+/// }
+///
+/// let streamlink = Streamlink::from_strs(vec!["https://twitch.tv/gogcom"]).unwrap();
+/// for result in streamlink.status_with(&AlwaysOnline) {
+///     assert_eq!(StreamStatus::Online, result.status);
+/// }
+/// ```
+pub trait StatusProvider: Sync {
+    /// Checks if `stream` is online.
+    fn check(&self, stream: &Stream) -> Result<StreamStatus>;
+
+    /// Checks if each of `streams` is online, returning their statuses in
+    /// the same order.
+    ///
+    /// By default, checks the streams one by one. Override this if the
+    /// provider can check many streams at once.
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        streams.iter().map(|stream| self.check(stream)).collect()
+    }
+
+    /// Returns how many streams [`check_many`] should be given at once.
+    ///
+    /// [`check_many`]: #method.check_many
+    fn batch_size(&self) -> usize {
+        1
+    }
+
+    /// Checks if `stream` is online, and what it's about.
+    ///
+    /// By default, only the status is known.
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        self.check(stream).map(StreamInfo::from)
+    }
+
+    /// Like [`check_many`](#method.check_many), but with the details of the
+    /// streams.
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        streams.iter().map(|stream| self.info(stream)).collect()
+    }
+}
+
+impl<P: StatusProvider + ?Sized> StatusProvider for &P {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        (**self).check(stream)
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        (**self).check_many(streams)
+    }
+
+    fn batch_size(&self) -> usize {
+        (**self).batch_size()
+    }
+
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        (**self).info(stream)
+    }
+
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        (**self).info_many(streams)
+    }
+}
+
+/// Retries the checks of `provider` that failed or came back `Unknown`,
+/// waiting twice as long before each retry.
+///
+/// Streams that were found to be offline aren't checked again.
+#[derive(Debug)]
+pub struct Retry<P> {
+    pub provider: P,
+    /// How many times to retry a check.
+    pub retries: u32,
+    /// How long to wait before the first retry.
+    pub delay: Duration,
+}
+
+impl<P: StatusProvider> Retry<P> {
+    /// Runs `check` again while its `result` is a transient failure.
+    ///
+    /// Errors of retried checks are wrapped in `CheckFailed`.
+    fn retry<T, F>(
+        &self,
+        mut result: Result<T>,
+        status: fn(&T) -> StreamStatus,
+        check: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Result<T>,
+    {
+        let mut attempts = 1;
+        let mut delay = self.delay;
+        while attempts <= self.retries && is_transient(result.as_ref().map(status)) {
+            thread::sleep(delay);
+            delay *= 2;
+            result = check();
+            attempts += 1;
+        }
+        if attempts > 1 {
+            result.chain_err(|| ErrorKind::CheckFailed(attempts))
+        } else {
+            result
+        }
+    }
+}
+
+impl<P: StatusProvider> StatusProvider for Retry<P> {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        self.retry(
+            self.provider.check(stream),
+            |&status| status,
+            || self.provider.check(stream),
+        )
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        self.provider
+            .check_many(streams)
+            .into_iter()
+            .zip(streams)
+            .map(|(status, stream)| {
+                self.retry(status, |&status| status, || self.provider.check(stream))
+            })
+            .collect()
+    }
+
+    fn batch_size(&self) -> usize {
+        self.provider.batch_size()
+    }
+
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        self.retry(
+            self.provider.info(stream),
+            |info| info.status,
+            || self.provider.info(stream),
+        )
+    }
+
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        self.provider
+            .info_many(streams)
+            .into_iter()
+            .zip(streams)
+            .map(|(info, stream)| {
+                self.retry(info, |info| info.status, || self.provider.info(stream))
+            })
+            .collect()
+    }
+}
+
+/// Spaces out checks against the same platform, so they don't trip its rate
+/// limits. Checks against different platforms aren't held back.
+///
+/// It can be shared between threads, which then take turns.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    interval: Duration,
+    /// When each platform may be checked next.
+    next: Mutex<HashMap<PlatformKind, Instant>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter letting through a check against each platform
+    /// every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Waits until `platform` may be checked.
+    pub fn wait(&self, platform: PlatformKind) {
+        let delay = self.reserve(platform);
+        if delay > Duration::from_secs(0) {
+            thread::sleep(delay);
+        }
+    }
+
+    /// Takes the next turn to check `platform`, returning how long to wait
+    /// for it, so it can be waited for without blocking.
+    pub(crate) fn reserve(&self, platform: PlatformKind) -> Duration {
+        if self.interval == Duration::from_secs(0) {
+            return Duration::from_secs(0);
+        }
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let at = next.get(&platform).map_or(now, |&at| at.max(now));
+        next.insert(platform, at + self.interval);
+        at - now
+    }
+
+    /// Waits until all platforms of `streams` may be checked.
+    fn wait_all(&self, streams: &[&Stream]) {
+        let mut platforms: Vec<PlatformKind> = vec![];
+        for stream in streams {
+            if !platforms.contains(&stream.platform()) {
+                platforms.push(stream.platform());
+            }
+        }
+        for platform in platforms {
+            self.wait(platform);
+        }
+    }
+}
+
+/// Checks streams with `provider`, waiting for `limiter` before each check.
+///
+/// Batches are spaced out as a whole, once for each platform in them.
+#[derive(Debug)]
+pub struct RateLimited<'a, P> {
+    pub provider: P,
+    pub limiter: &'a RateLimiter,
+}
+
+impl<'a, P: StatusProvider> StatusProvider for RateLimited<'a, P> {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        self.limiter.wait(stream.platform());
+        self.provider.check(stream)
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        if self.provider.batch_size() > 1 {
+            self.limiter.wait_all(streams);
+            self.provider.check_many(streams)
+        } else {
+            streams.iter().map(|stream| self.check(stream)).collect()
+        }
+    }
+
+    fn batch_size(&self) -> usize {
+        self.provider.batch_size()
+    }
+
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        self.limiter.wait(stream.platform());
+        self.provider.info(stream)
+    }
+
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        if self.provider.batch_size() > 1 {
+            self.limiter.wait_all(streams);
+            self.provider.info_many(streams)
+        } else {
+            streams.iter().map(|stream| self.info(stream)).collect()
+        }
+    }
+}
+
+/// Checks streams with `provider`, unless `cache` has their status.
+///
+/// The statuses of checked streams are added to the cache, which is saved
+/// after every batch. Without a cache, every stream is checked.
+#[derive(Debug)]
+pub struct Cached<'a, P> {
+    pub provider: P,
+    pub cache: Option<&'a StatusCache>,
+}
+
+impl<'a, P: StatusProvider> Cached<'a, P> {
+    /// Checks the streams missing from the cache with `check`, and makes
+    /// results of the cached statuses with `from_status`.
+    fn check_cached<T, C>(
+        &self,
+        streams: &[&Stream],
+        status: fn(&T) -> StreamStatus,
+        from_status: fn(StreamStatus) -> T,
+        check: C,
+    ) -> Vec<Result<(T, Option<SystemTime>)>>
+    where
+        C: FnOnce(&[&Stream]) -> Vec<Result<T>>,
+    {
+        let cache = match self.cache {
+            Some(cache) => cache,
+            None => return fresh(check(streams)),
+        };
+        let cached: Vec<Option<CacheEntry>> =
+            streams.iter().map(|stream| cache.get(stream)).collect();
+        let missing: Vec<&Stream> = streams
+            .iter()
+            .zip(&cached)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(&stream, _)| stream)
+            .collect();
+        let mut checked = Vec::new().into_iter();
+        if !missing.is_empty() {
+            let results = check(&missing);
+            for (stream, result) in missing.iter().zip(&results) {
+                if let Ok(ref result) = *result {
+                    cache.insert(stream, status(result));
+                }
+            }
+            // The cache only saves checking again, so failing to save it
+            // shouldn't fail the checks.
+            let _ = cache.save();
+            checked = fresh(results).into_iter();
+        }
+        cached
+            .into_iter()
+            .map(|cached| match cached {
+                Some(entry) => Ok((from_status(entry.status), Some(entry.time()))),
+                None => checked.next().expect("missing result"),
+            })
+            .collect()
+    }
+
+    /// Checks `streams`, telling when each status was checked and whether it
+    /// was cached.
+    pub(crate) fn check_results<'s>(&self, streams: &[&'s Stream]) -> Vec<CheckResult<'s>> {
+        let results = self.check_cached(
+            streams,
+            |&status| status,
+            |status| status,
+            |streams| self.provider.check_many(streams),
+        );
+        let now = SystemTime::now();
+        streams
+            .iter()
+            .zip(results)
+            .map(|(&stream, result)| {
+                let (status, cached_at) = result.unwrap_or((StreamStatus::Unknown, None));
+                CheckResult {
+                    stream,
+                    status,
+                    checked_at: cached_at.unwrap_or(now),
+                    from_cache: cached_at.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Like [`info_many`](trait.StatusProvider.html#method.info_many), but
+    /// with the time each cached status was checked.
+    pub(crate) fn info_many_cached(
+        &self,
+        streams: &[&Stream],
+    ) -> Vec<Result<(StreamInfo, Option<SystemTime>)>> {
+        self.check_cached(
+            streams,
+            |info| info.status,
+            StreamInfo::from,
+            |streams| self.provider.info_many(streams),
+        )
+    }
+}
+
+/// Marks `results` as checked just now.
+fn fresh<T>(results: Vec<Result<T>>) -> Vec<Result<(T, Option<SystemTime>)>> {
+    results
+        .into_iter()
+        .map(|result| result.map(|result| (result, None)))
+        .collect()
+}
+
+impl<'a, P: StatusProvider> StatusProvider for Cached<'a, P> {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        self.check_many(&[stream]).remove(0)
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        self.check_cached(
+            streams,
+            |&status| status,
+            |status| status,
+            |streams| self.provider.check_many(streams),
+        )
+        .into_iter()
+        .map(|result| result.map(|(status, _)| status))
+        .collect()
+    }
+
+    fn batch_size(&self) -> usize {
+        self.provider.batch_size()
+    }
+
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        self.info_many(&[stream]).remove(0)
+    }
+
+    /// Only the status of cached streams is known.
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        self.info_many_cached(streams)
+            .into_iter()
+            .map(|result| result.map(|(info, _)| info))
+            .collect()
+    }
+}
+
+/// How many streams the default provider checks with one run of the
+/// backend.
+pub const DEFAULT_BATCH_SIZE: usize = 10;
+
+/// Checks if checking again might give a different `status`.
+fn is_transient(status: ::std::result::Result<StreamStatus, &Error>) -> bool {
+    match status {
+        Ok(status) => status == StreamStatus::Unknown,
+        Err(&Error(ErrorKind::BackendMissing(_), _))
+        | Err(&Error(ErrorKind::TeamExpansion(_), _)) => false,
+        Err(_) => true,
+    }
+}
+
+/// Checks streams by running a backend program, `youtube-dl` by default.
+///
+/// This is what [`Stream::status`] uses.
+///
+/// [`Stream::status`]: struct.Stream.html#method.status
+#[derive(Debug)]
+pub struct YoutubeDlProvider {
+    /// How the backend is run.
+    pub options: CheckOptions,
+    /// How many streams to check with one run of the backend, if it can
+    /// check many at once (like `youtube-dl`). 1 checks them one by one.
+    pub batch_size: usize,
+}
+
+impl YoutubeDlProvider {
+    /// Creates a provider running the backend as set in `options`, checking
+    /// streams one by one.
+    pub fn new(options: CheckOptions) -> Self {
+        Self {
+            options,
+            batch_size: 1,
+        }
+    }
+
+    pub(crate) fn options_mut(&mut self) -> &mut CheckOptions {
+        &mut self.options
+    }
+
+    /// Checks `streams` in batches, turning the details of each stream into
+    /// a result with `from_info`.
+    ///
+    /// Streams the backend told nothing about, streams with check settings of
+    /// their own, and every stream of a batch that failed, are checked on
+    /// their own with `single`.
+    fn check_batches<T, F>(
+        &self,
+        streams: &[&Stream],
+        from_info: fn(StreamInfo) -> T,
+        single: F,
+    ) -> Vec<Result<T>>
+    where
+        F: Fn(&Stream) -> Result<T>,
+    {
+        let mut results = Vec::with_capacity(streams.len());
+        for chunk in streams.chunks(self.batch_size()) {
+            // Teams can't be checked, so leave them to fail on their own.
+            let batch: Vec<&Stream> = chunk
+                .iter()
+                .cloned()
+                .filter(|stream| batchable(stream))
+                .collect();
+            let mut infos = match batch.len() {
+                0 | 1 => vec![None; batch.len()],
+                _ => self
+                    .options
+                    .backend
+                    .info_batch(&batch, &self.options.backend_args(), self.options.timeout)
+                    .unwrap_or_else(|_| vec![None; batch.len()]),
+            }
+            .into_iter();
+            for stream in chunk {
+                let info = if batchable(stream) {
+                    infos.next().expect("missing info")
+                } else {
+                    None
+                };
+                results.push(match info {
+                    Some(info) => Ok(from_info(info)),
+                    None => single(stream),
+                });
+            }
+        }
+        results
+    }
+}
+
+/// Checks if `stream` may be checked along with others in a batch, which
+/// shares the options of the provider.
+fn batchable(stream: &Stream) -> bool {
+    stream.platform() != PlatformKind::TwitchTeam && !stream.has_check_settings()
+}
+
+impl Default for YoutubeDlProvider {
+    fn default() -> Self {
+        Self::new(CheckOptions::default())
+    }
+}
+
+impl StatusProvider for YoutubeDlProvider {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        stream.status_with(&self.options)
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        self.check_batches(streams, |info| info.status, |stream| self.check(stream))
+    }
+
+    fn batch_size(&self) -> usize {
+        if self.options.backend.can_batch() {
+            self.batch_size.max(1)
+        } else {
+            1
+        }
+    }
+
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        stream.info_with(&self.options)
+    }
+
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        self.check_batches(streams, |info| info, |stream| self.info(stream))
+    }
+}
+
+/// Checks Twitch channels through the Twitch API, without running a backend.
+///
+/// Other streams are checked with `fallback`, whose proxy is used for the
+/// API too.
+#[cfg(feature = "http")]
+#[derive(Debug, Default)]
+pub struct TwitchApiProvider {
+    /// Checks streams not on Twitch.
+    pub fallback: YoutubeDlProvider,
+}
+
+#[cfg(feature = "http")]
+impl TwitchApiProvider {
+    /// Creates a provider running the fallback backend as set in `options`.
+    pub fn new(options: CheckOptions) -> Self {
+        Self {
+            fallback: YoutubeDlProvider::new(options),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl StatusProvider for TwitchApiProvider {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        match twitch_login(stream) {
+            Some(login) => twitch::stream_status(login, self.fallback.options.proxy.as_deref()),
+            None => self.fallback.check(stream),
+        }
+    }
+}
+
+/// Checks Twitch channels through the Twitch Helix API, many at once.
+///
+/// Other streams are checked with `fallback`.
+#[cfg(feature = "twitch-api")]
+#[derive(Debug)]
+pub struct HelixProvider {
+    pub(crate) helix: Helix,
+    /// Checks streams not on Twitch.
+    pub fallback: YoutubeDlProvider,
+}
+
+#[cfg(feature = "twitch-api")]
+impl HelixProvider {
+    /// Creates a provider authenticating as the Twitch app with `client_id`
+    /// and `client_secret`, and running the fallback backend as set in
+    /// `options`.
+    pub fn new(client_id: String, client_secret: String, options: CheckOptions) -> Self {
+        Self {
+            helix: Helix::new(client_id, client_secret, options.proxy.clone()),
+            fallback: YoutubeDlProvider::new(options),
+        }
+    }
+}
+
+#[cfg(feature = "twitch-api")]
+impl StatusProvider for HelixProvider {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        self.check_many(&[stream]).remove(0)
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        check_many_with_helix(&self.helix, streams, |stream| self.fallback.check(stream))
+    }
+
+    fn batch_size(&self) -> usize {
+        HELIX_BATCH_SIZE
+    }
+}
+
+/// Checks the Twitch channels among `streams` in a single Helix request,
+/// and the other streams with `fallback`.
+#[cfg(feature = "twitch-api")]
+fn check_many_with_helix<F>(
+    helix: &Helix,
+    streams: &[&Stream],
+    fallback: F,
+) -> Vec<Result<StreamStatus>>
+where
+    F: Fn(&Stream) -> Result<StreamStatus>,
+{
+    let logins: Vec<&str> = streams.iter().filter_map(|s| twitch_login(s)).collect();
+    let live = helix.live_logins(&logins);
+    streams
+        .iter()
+        .map(|stream| match (twitch_login(stream), &live) {
+            (Some(login), Ok(live)) if live.contains(&login.to_lowercase()) => {
+                Ok(StreamStatus::Online)
+            }
+            (Some(_), Ok(_)) => Ok(StreamStatus::Offline),
+            (Some(_), Err(e)) => Err(e.to_string().into()),
+            (None, _) => fallback(stream),
+        })
+        .collect()
+}
+
+/// Returns the login of `stream` if it's a Twitch channel.
+#[cfg(feature = "http")]
+fn twitch_login(stream: &Stream) -> Option<&str> {
+    match stream.platform() {
+        PlatformKind::Twitch => stream.name(),
+        _ => None,
+    }
+}
+
+/// Checks YouTube channels and videos through the YouTube Data API.
+///
+/// Other streams are checked with `fallback`.
+#[cfg(feature = "youtube-api")]
+#[derive(Debug)]
+pub struct YoutubeApiProvider {
+    pub(crate) api: DataApi,
+    /// Checks streams not on YouTube.
+    pub fallback: YoutubeDlProvider,
+}
+
+#[cfg(feature = "youtube-api")]
+impl YoutubeApiProvider {
+    /// Creates a provider calling the API with `api_key`, and running the
+    /// fallback backend as set in `options`.
+    pub fn new(api_key: String, options: CheckOptions) -> Self {
+        Self {
+            api: DataApi::new(api_key, options.proxy.clone()),
+            fallback: YoutubeDlProvider::new(options),
+        }
+    }
+}
+
+#[cfg(feature = "youtube-api")]
+impl StatusProvider for YoutubeApiProvider {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        match stream.platform() {
+            PlatformKind::Youtube => self.api.stream_status(stream),
+            _ => self.fallback.check(stream),
+        }
+    }
+}
+
+/// The provider used by [`Streamlink`] unless another one is set.
+///
+/// Checks streams through platform APIs where the enabled features (and
+/// configured credentials) allow, and by running the backend otherwise.
+///
+/// [`Streamlink`]: struct.Streamlink.html
+#[derive(Debug)]
+pub(crate) struct DefaultProvider {
+    subprocess: YoutubeDlProvider,
+    #[cfg(feature = "twitch-api")]
+    pub(crate) helix: Option<Helix>,
+    #[cfg(feature = "youtube-api")]
+    pub(crate) youtube: Option<DataApi>,
+}
+
+impl Default for DefaultProvider {
+    fn default() -> Self {
+        Self::new(CheckOptions::default())
+    }
+}
+
+impl DefaultProvider {
+    pub fn new(options: CheckOptions) -> Self {
+        Self {
+            subprocess: YoutubeDlProvider {
+                options,
+                batch_size: DEFAULT_BATCH_SIZE,
+            },
+            #[cfg(feature = "twitch-api")]
+            helix: None,
+            #[cfg(feature = "youtube-api")]
+            youtube: None,
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub fn options(&self) -> &CheckOptions {
+        &self.subprocess.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut CheckOptions {
+        self.subprocess.options_mut()
+    }
+
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.subprocess.batch_size = batch_size;
+    }
+
+    /// Checks if `stream` is checked through a platform API.
+    #[cfg_attr(not(feature = "http"), allow(unused_variables))]
+    fn uses_api(&self, stream: &Stream) -> bool {
+        #[cfg(feature = "youtube-api")]
+        {
+            if stream.platform() == PlatformKind::Youtube && self.youtube.is_some() {
+                return true;
+            }
+        }
+        #[cfg(feature = "http")]
+        {
+            if twitch_login(stream).is_some() {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Checks the streams handled by a platform API with `api`, and the
+    /// others with `backend`, keeping the results in the order of `streams`.
+    fn dispatch<T, A, B>(&self, streams: &[&Stream], api: A, backend: B) -> Vec<Result<T>>
+    where
+        A: FnOnce(&[&Stream]) -> Vec<Result<T>>,
+        B: FnOnce(&[&Stream]) -> Vec<Result<T>>,
+    {
+        let (api_streams, backend_streams): (Vec<&Stream>, Vec<&Stream>) =
+            streams.iter().partition(|stream| self.uses_api(stream));
+        let mut api_results = api(&api_streams).into_iter();
+        let mut backend_results = backend(&backend_streams).into_iter();
+        streams
+            .iter()
+            .map(|stream| {
+                if self.uses_api(stream) {
+                    api_results.next()
+                } else {
+                    backend_results.next()
+                }
+                .expect("missing result")
+            })
+            .collect()
+    }
+
+    /// Checks `streams` through platform APIs, in one go with Helix.
+    fn check_api(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        #[cfg(feature = "twitch-api")]
+        {
+            if let Some(ref helix) = self.helix {
+                return check_many_with_helix(helix, streams, |stream| self.check_one(stream));
+            }
+        }
+        streams
+            .iter()
+            .map(|stream| self.check_one(stream))
+            .collect()
+    }
+
+    /// Checks `stream` without Helix.
+    fn check_one(&self, stream: &Stream) -> Result<StreamStatus> {
+        #[cfg(feature = "youtube-api")]
+        {
+            if let (PlatformKind::Youtube, Some(ref api)) = (stream.platform(), &self.youtube) {
+                return api.stream_status(stream);
+            }
+        }
+        #[cfg(feature = "http")]
+        {
+            if let Some(login) = twitch_login(stream) {
+                return twitch::stream_status(login, self.subprocess.options.proxy.as_deref());
+            }
+        }
+        self.subprocess.check(stream)
+    }
+}
+
+impl StatusProvider for DefaultProvider {
+    fn check(&self, stream: &Stream) -> Result<StreamStatus> {
+        self.check_many(&[stream]).remove(0)
+    }
+
+    fn check_many(&self, streams: &[&Stream]) -> Vec<Result<StreamStatus>> {
+        self.dispatch(
+            streams,
+            |streams| self.check_api(streams),
+            |streams| self.subprocess.check_many(streams),
+        )
+    }
+
+    fn batch_size(&self) -> usize {
+        #[cfg(feature = "twitch-api")]
+        {
+            if self.helix.is_some() {
+                return HELIX_BATCH_SIZE.max(self.subprocess.batch_size());
+            }
+        }
+        self.subprocess.batch_size()
+    }
+
+    fn info(&self, stream: &Stream) -> Result<StreamInfo> {
+        self.info_many(&[stream]).remove(0)
+    }
+
+    /// Platform APIs only tell the status, so only streams checked with the
+    /// backend have details.
+    fn info_many(&self, streams: &[&Stream]) -> Vec<Result<StreamInfo>> {
+        self.dispatch(
+            streams,
+            |streams| {
+                self.check_api(streams)
+                    .into_iter()
+                    .map(|status| status.map(StreamInfo::from))
+                    .collect()
+            },
+            |streams| self.subprocess.info_many(streams),
+        )
+    }
+}
@@ -0,0 +1,152 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::process::{Child, ExitStatus};
+use std::time::SystemTime;
+
+use errors::*;
+use template::{tokenize, unknown_placeholder, Token};
+use {format_timestamp, Backend, CheckOptions, Config, Stream};
+
+/// What recordings are named by default, see
+/// [`RecordOptions::file_name`](struct.RecordOptions.html#structfield.file_name).
+pub const DEFAULT_RECORD_NAME: &str = "{name}-{date}";
+
+/// How to record a stream with [`Stream::record`](struct.Stream.html#method.record).
+#[derive(Debug, Clone)]
+pub struct RecordOptions {
+    /// The backend to record with, in its download mode, e.g. `yt-dlp -o`.
+    pub backend: Backend,
+    /// Extra arguments passed to the backend, before its own.
+    pub args: Vec<String>,
+    /// The directory to record into, created if needed.
+    pub dir: PathBuf,
+    /// What to name the recording, without its extension. `{name}` stands
+    /// for the name of the stream, `{date}` for when the recording started,
+    /// as e.g. `2024-01-31_12-00-00`, and `{{` and `}}` for `{` and `}`.
+    pub file_name: String,
+    /// The quality to record the stream in, e.g. `"best"` or `"720p"`. Only
+    /// `streamlink` is told it; `youtube-dl` picks the best one itself.
+    pub quality: String,
+}
+
+impl RecordOptions {
+    /// The options to record `stream` with, as set in `config`, running the
+    /// backend as set in `check`.
+    pub fn for_stream(config: &Config, check: &CheckOptions, stream: &Stream) -> Self {
+        RecordOptions {
+            backend: check.backend.clone(),
+            args: check.backend_args(),
+            dir: config
+                .record_dir
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(".")),
+            file_name: config
+                .record_name
+                .clone()
+                .unwrap_or_else(|| DEFAULT_RECORD_NAME.into()),
+            quality: config.quality_for(stream).into(),
+        }
+    }
+
+    /// Returns where to record `stream` to, if it's started at `time`.
+    pub(crate) fn path(&self, stream: &Stream, time: SystemTime) -> Result<PathBuf> {
+        let date = format_timestamp(time)
+            .trim_end_matches('Z')
+            .replace('T', "_")
+            .replace(':', "-");
+        let name = file_name(&self.file_name, &sanitize(stream.display_name()), &date)?;
+        Ok(self.dir.join(format!("{}.ts", name)))
+    }
+
+    /// Creates the directory to record into, if needed, and makes sure it
+    /// can be written to, failing with `RecordDir` otherwise.
+    pub(crate) fn prepare_dir(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| check_writable(&self.dir))
+            .chain_err(|| ErrorKind::RecordDir(self.dir.display().to_string()))
+    }
+}
+
+/// Makes sure files can be written to `dir`, by writing one and removing it
+/// again.
+pub(crate) fn check_writable(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(format!(".streamlink-rs.{}.tmp", process::id()));
+    fs::write(&probe, "").and_then(|()| fs::remove_file(&probe))
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        RecordOptions {
+            backend: Backend::default(),
+            args: vec![],
+            dir: PathBuf::from("."),
+            file_name: DEFAULT_RECORD_NAME.into(),
+            quality: Config::default().quality,
+        }
+    }
+}
+
+/// A stream being recorded, started with
+/// [`Stream::record`](struct.Stream.html#method.record).
+///
+/// The backend keeps recording until the stream ends, or it's stopped.
+#[derive(Debug)]
+pub struct Recording {
+    pub(crate) child: Child,
+    pub(crate) path: PathBuf,
+}
+
+impl Recording {
+    /// Where the stream is recorded to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The backend recording the stream.
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Waits for the recording to end, and returns how the backend exited.
+    pub fn wait(mut self) -> Result<ExitStatus> {
+        Ok(self.child.wait()?)
+    }
+
+    /// Stops the recording, and returns how the backend exited.
+    pub fn stop(mut self) -> Result<ExitStatus> {
+        // The backend may have exited in the meantime, so don't fail if
+        // there's nothing left to kill.
+        let _ = self.child.kill();
+        Ok(self.child.wait()?)
+    }
+}
+
+/// Fills in `template` with `name` and `date`, failing with
+/// `InvalidTemplate` on unknown placeholders and unmatched braces.
+fn file_name(template: &str, name: &str, date: &str) -> Result<String> {
+    let mut file_name = String::new();
+    for token in tokenize(template)? {
+        match token {
+            Token::Text(ref text) => file_name.push_str(text),
+            Token::Placeholder(ref placeholder) => match placeholder.as_str() {
+                "name" => file_name.push_str(name),
+                "date" => file_name.push_str(date),
+                _ => return Err(unknown_placeholder(template, placeholder)),
+            },
+        }
+    }
+    Ok(file_name)
+}
+
+/// Replaces what can't be in a file name in `name`, e.g. the slashes of a
+/// stream shown as its URL.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde_json;
+use url::Url;
+
+use cache::write_atomic;
+use errors::*;
+use {load_config, Config, ImportSummary, PlatformRegistry, Stream, StreamConfig};
+
+/// How [`export_streams`](fn.export_streams.html) writes the streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    /// An OPML outline, with an `<outline>` per stream, its URL as the
+    /// `xmlUrl` and its tags as the `category`, as read by feed readers.
+    Opml,
+    /// A JSON array of objects with the `name`, `url`, `platform` and `tags`
+    /// of each stream.
+    Json,
+}
+
+impl ListFormat {
+    /// The format a list at `path` is in by its extension, if it tells:
+    /// `.opml` or `.xml` for OPML, and `.json` for JSON.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "opml" | "xml" => Some(ListFormat::Opml),
+            "json" => Some(ListFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A stream as listed by [`export_streams`](fn.export_streams.html).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct ListedStream {
+    /// The alias of the stream, or else the name in its URL. Left out of
+    /// lists written elsewhere, in which case it's named by its URL.
+    #[serde(default)]
+    name: Option<String>,
+    url: String,
+    /// Only written, as it follows from the URL.
+    #[serde(default, skip_deserializing)]
+    platform: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Writes the streams in the configs in `config_paths`, merged as they are
+/// for [`run`](fn.run.html), in `format` to `output`, or else to stdout.
+///
+/// Teams are listed as is, not as their channels.
+pub fn export_streams<P: AsRef<Path>>(
+    config_paths: &[P],
+    format: ListFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    match output {
+        Some(path) => {
+            let mut list = vec![];
+            export_streams_to(config_paths, format, &mut list)?;
+            write_atomic(path, &String::from_utf8_lossy(&list))
+                .chain_err(|| ErrorKind::StreamListWrite(path.display().to_string()))
+        }
+        None => export_streams_to(config_paths, format, &mut io::stdout()),
+    }
+}
+
+/// Like [`export_streams`](fn.export_streams.html), but writes to `out`.
+pub fn export_streams_to<P, W>(config_paths: &[P], format: ListFormat, out: &mut W) -> Result<()>
+where
+    P: AsRef<Path>,
+    W: Write,
+{
+    let config = load_config(config_paths)?;
+    let streams = listed_streams(&config)?;
+    let list = match format {
+        ListFormat::Opml => render_opml(&streams),
+        ListFormat::Json => {
+            serde_json::to_string_pretty(&streams).chain_err(|| "unable to serialize streams")?
+                + "\n"
+        }
+    };
+    out.write_all(list.as_bytes())?;
+    Ok(())
+}
+
+/// Adds the streams listed in `list_path`, as written by
+/// [`export_streams`](fn.export_streams.html) in either format, to the
+/// config in `config_path` like
+/// [`Config::import_streams`](struct.Config.html#method.import_streams),
+/// saving it in place.
+///
+/// Comments in the config are not kept.
+pub fn import_streams_from<P, L>(config_path: P, list_path: L) -> Result<ImportSummary>
+where
+    P: AsRef<Path>,
+    L: AsRef<Path>,
+{
+    let list_path = list_path.as_ref();
+    let list = fs::read_to_string(list_path)
+        .chain_err(|| ErrorKind::StreamListRead(list_path.display().to_string()))?;
+    let format = ListFormat::from_path(list_path).unwrap_or_else(|| sniff(&list));
+    let streams = match format {
+        ListFormat::Opml => parse_opml(&list),
+        ListFormat::Json => serde_json::from_str(&list).map_err(|e| e.to_string()),
+    }
+    .map_err(|reason| ErrorKind::StreamListParse(list_path.display().to_string(), reason))?;
+    let streams = streams.into_iter().map(stream_config).collect();
+    Config::import_streams_to_file(config_path, streams)
+}
+
+/// Returns the streams in `config`, in the order they're in there.
+fn listed_streams(config: &Config) -> Result<Vec<ListedStream>> {
+    let mut registry = PlatformRegistry::new();
+    registry.register_config(&config.platforms);
+    let entries = config
+        .stream_urls
+        .iter()
+        .map(|url| (url, None, &[][..]))
+        .chain(
+            config
+                .streams
+                .iter()
+                .map(|stream| (&stream.url, stream.name.as_ref(), stream.tags.as_slice())),
+        );
+    let mut streams = vec![];
+    for (url, alias, tags) in entries {
+        let parsed = Url::parse(url).chain_err(|| ErrorKind::UrlParse(url.clone()))?;
+        let stream = Stream::from_url_with_options(parsed, &registry, config.allow_other)?;
+        streams.push(ListedStream {
+            name: alias.cloned().or_else(|| stream.name().map(String::from)),
+            url: url.clone(),
+            platform: stream.platform_id().into(),
+            tags: tags.to_vec(),
+        });
+    }
+    Ok(streams)
+}
+
+/// Returns the config of the stream in `listed`, named by its name unless
+/// that's the one in its URL anyway.
+fn stream_config(listed: ListedStream) -> StreamConfig {
+    let mut stream = StreamConfig::new(listed.url);
+    let url_name = Stream::from_string(stream.url.clone())
+        .ok()
+        .and_then(|parsed| parsed.name().map(String::from));
+    stream.name = listed.name.filter(|name| Some(name) != url_name.as_ref());
+    stream.tags = listed.tags;
+    stream
+}
+
+/// Tells the format of `list` by how it starts, as OPML is XML.
+fn sniff(list: &str) -> ListFormat {
+    if list.trim_start().starts_with('<') {
+        ListFormat::Opml
+    } else {
+        ListFormat::Json
+    }
+}
+
+/// Formats `streams` as an OPML 2.0 document.
+fn render_opml(streams: &[ListedStream]) -> String {
+    let mut opml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"2.0\">\n  \
+         <head>\n    <title>streamlink-rs streams</title>\n  </head>\n  \
+         <body>\n",
+    );
+    for stream in streams {
+        let name = escape_xml(stream.name.as_ref().unwrap_or(&stream.url));
+        opml.push_str(&format!(
+            "    <outline type=\"link\" text=\"{0}\" title=\"{0}\" xmlUrl=\"{1}\"",
+            name,
+            escape_xml(&stream.url)
+        ));
+        if !stream.tags.is_empty() {
+            opml.push_str(&format!(
+                " category=\"{}\"",
+                escape_xml(&stream.tags.join(","))
+            ));
+        }
+        opml.push_str("/>\n");
+    }
+    opml.push_str("  </body>\n</opml>\n");
+    opml
+}
+
+/// Reads the streams in the OPML document `opml`: the outlines with an
+/// `xmlUrl`, named by their `title` or `text`, and tagged by their
+/// `category`. Outlines without one, e.g. folders, are skipped.
+fn parse_opml(opml: &str) -> ::std::result::Result<Vec<ListedStream>, String> {
+    if !opml.contains("<opml") {
+        return Err("not an OPML document".into());
+    }
+    let mut streams = vec![];
+    let mut rest = opml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+        // Outlines in comments and CDATA sections aren't read.
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").ok_or("unclosed comment")?;
+            rest = &comment[end + "-->".len()..];
+            continue;
+        }
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").ok_or("unclosed CDATA section")?;
+            rest = &cdata[end + "]]>".len()..];
+            continue;
+        }
+        let tag = match rest.strip_prefix("<outline") {
+            Some(tag) if tag.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>') => {
+                tag
+            }
+            // Other tags, e.g. `<outlineGroup>`.
+            _ => {
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let end = tag_end(tag).ok_or("unclosed <outline>")?;
+        let mut attributes = parse_attributes(&tag[..end])?;
+        rest = &tag[end..];
+        let url = match attributes.remove("xmlUrl") {
+            Some(url) => url,
+            None => continue,
+        };
+        let name = attributes
+            .remove("title")
+            .or_else(|| attributes.remove("text"))
+            .filter(|name| !name.is_empty() && *name != url);
+        let tags = attributes
+            .remove("category")
+            .map(|category| {
+                category
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        streams.push(ListedStream {
+            name,
+            url,
+            platform: String::new(),
+            tags,
+        });
+    }
+    Ok(streams)
+}
+
+/// Returns where the tag starting `tag` ends, at its `>`, skipping the ones
+/// in quoted attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (c, quote) {
+            ('>', None) => return Some(i),
+            ('"', None) | ('\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Reads the attributes in `attributes`, e.g. `text="a" xmlUrl='b' /`,
+/// unescaping their values.
+fn parse_attributes(attributes: &str) -> ::std::result::Result<HashMap<String, String>, String> {
+    let mut parsed = HashMap::new();
+    let mut rest = attributes.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    while !rest.is_empty() {
+        let equals = rest
+            .find('=')
+            .ok_or_else(|| format!("attribute without a value in <outline{}>", attributes))?;
+        let name = rest[..equals].trim();
+        let value = rest[equals + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|&c| c == '"' || c == '\'')
+            .ok_or_else(|| format!("unquoted value of '{}'", name))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or_else(|| format!("unclosed value of '{}'", name))?;
+        parsed.insert(name.to_string(), unescape_xml(&value[1..=end])?);
+        rest = value[end + 2..].trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+    }
+    Ok(parsed)
+}
+
+/// Escapes what can't be in a quoted XML attribute value in `text`.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Replaces the XML entities and character references in `text` with what
+/// they stand for.
+fn unescape_xml(text: &str) -> ::std::result::Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or_else(|| format!("unterminated entity in '{}'", text))?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match (entity.strip_prefix("#x"), entity.strip_prefix('#')) {
+                (Some(hex), _) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                (None, Some(decimal)) => decimal.parse().ok().and_then(char::from_u32),
+                (None, None) => None,
+            },
+        };
+        unescaped.push(c.ok_or_else(|| format!("unknown entity '&{};'", entity))?);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
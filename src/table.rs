@@ -0,0 +1,97 @@
+use std::iter;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use {paint_status, StatusReport};
+
+/// How many columns a name takes up in a table before it's cut short.
+const MAX_NAME_WIDTH: usize = 24;
+
+/// The column of the statuses, which are colored.
+const STATUS_COLUMN: usize = 2;
+
+/// Formats `reports` as a table of their names, platforms, statuses, and
+/// titles if any has one, under a header, with the columns as wide as their
+/// widest cell. The statuses are colored if `color` is set.
+///
+/// Streams that went online or offline since the last run are starred, as
+/// with [`run`](fn.run.html).
+pub(crate) fn render_table(reports: &[StatusReport], color: bool) -> String {
+    if reports.is_empty() {
+        return String::new();
+    }
+    let titles = reports.iter().any(|report| report.title.is_some());
+    let mut header: Vec<String> = vec!["NAME".into(), "PLATFORM".into(), "STATUS".into()];
+    if titles {
+        header.push("TITLE".into());
+    }
+    let rows: Vec<Vec<String>> = reports
+        .iter()
+        .map(|report| {
+            let mark = if report.changed { "* " } else { "" };
+            let mut row = vec![
+                format!("{}{}", mark, truncate(&report.name, MAX_NAME_WIDTH)),
+                report.platform.clone(),
+                report.status.to_string(),
+            ];
+            if titles {
+                row.push(report.title.clone().unwrap_or_default());
+            }
+            row
+        })
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[column].width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let statuses = reports.iter().map(|report| Some(report.status));
+    let mut table = String::new();
+    for (row, status) in iter::once((&header, None)).chain(rows.iter().zip(statuses)) {
+        let mut line = String::new();
+        for (column, cell) in row.iter().enumerate() {
+            if column > 0 {
+                line.push_str("  ");
+            }
+            match status {
+                Some(status) if column == STATUS_COLUMN => {
+                    line.push_str(&paint_status(color, status))
+                }
+                _ => line.push_str(cell),
+            }
+            // Pad with plain spaces, so they don't count the escape codes
+            // of colors.
+            if column + 1 < row.len() {
+                line.push_str(&" ".repeat(widths[column] - cell.width()));
+            }
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Cuts `text` short with an ellipsis if it takes up more than `max`
+/// columns, counting wide characters, e.g. CJK, as two.
+fn truncate(text: &str, max: usize) -> String {
+    if text.width() <= max {
+        return text.into();
+    }
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let c_width = c.width().unwrap_or(0);
+        // Leave room for the ellipsis.
+        if width + c_width + 1 > max {
+            break;
+        }
+        width += c_width;
+        truncated.push(c);
+    }
+    truncated.push('…');
+    truncated
+}
@@ -0,0 +1,238 @@
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use errors::*;
+use {format_age, paint_status, StatusReport};
+
+/// The [`Template`](struct.Template.html) statuses are printed as by
+/// default, e.g. `* gogcom is online (checked 5m ago): <title>`.
+pub const DEFAULT_TEMPLATE: &str =
+    "{|mark| }{name} is {status}{ (checked |cached_age| ago)}{: |title|}";
+
+/// A line to print the status of each stream as, with placeholders for what
+/// is known about it, e.g. `{name}:{status}`.
+///
+/// The placeholders are `{name}`, `{url}`, `{platform}`, `{status}`,
+/// `{title}`, `{mark}`, a `*` if the stream went online or offline since the
+/// last run, `{cached_age}`, how long ago a cached status was checked, e.g.
+/// `5m`, and `{checked_at}`, as e.g. `2024-01-31T12:00:00Z`. Those that
+/// aren't known are empty. `{{` and `}}` stand for `{` and `}`.
+///
+/// A placeholder written as `{before|name|after}` is only surrounded by
+/// `before` and `after` if it isn't empty, e.g. `{: |title|}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field {
+        field: Field,
+        before: String,
+        after: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Url,
+    Platform,
+    Status,
+    Title,
+    Mark,
+    CachedAge,
+    CheckedAt,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "name" => Field::Name,
+            "url" => Field::Url,
+            "platform" => Field::Platform,
+            "status" => Field::Status,
+            "title" => Field::Title,
+            "mark" => Field::Mark,
+            "cached_age" => Field::CachedAge,
+            "checked_at" => Field::CheckedAt,
+            _ => return None,
+        })
+    }
+}
+
+impl Template {
+    /// Fills in the template for `report`, with the status colored if
+    /// `color` is set.
+    pub fn render(&self, report: &StatusReport, color: bool) -> String {
+        let mut line = String::new();
+        for part in &self.parts {
+            let (field, before, after) = match *part {
+                Part::Text(ref text) => {
+                    line.push_str(text);
+                    continue;
+                }
+                Part::Field {
+                    field,
+                    ref before,
+                    ref after,
+                } => (field, before, after),
+            };
+            let value = match field {
+                Field::Name => report.name.clone(),
+                Field::Url => report.url.clone(),
+                Field::Platform => report.platform.clone(),
+                Field::Status => paint_status(color, report.status),
+                Field::Title => report.title.clone().unwrap_or_default(),
+                Field::Mark if report.changed => "*".into(),
+                Field::Mark => String::new(),
+                Field::CachedAge => report
+                    .cached_at
+                    .and_then(|time| time.elapsed().ok())
+                    .map(format_age)
+                    .unwrap_or_default(),
+                Field::CheckedAt => report.checked_at.map(format_timestamp).unwrap_or_default(),
+            };
+            if !value.is_empty() {
+                line.push_str(before);
+                line.push_str(&value);
+                line.push_str(after);
+            }
+        }
+        line
+    }
+}
+
+impl Default for Template {
+    /// The [`DEFAULT_TEMPLATE`](constant.DEFAULT_TEMPLATE.html).
+    fn default() -> Self {
+        DEFAULT_TEMPLATE
+            .parse()
+            .expect("the default template is valid")
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    /// Parses `template`, failing with `InvalidTemplate` on unknown
+    /// placeholders and unmatched braces.
+    fn from_str(template: &str) -> Result<Self> {
+        let invalid = |reason: String| ErrorKind::InvalidTemplate(template.into(), reason);
+        let mut parts = vec![];
+        for token in tokenize(template)? {
+            let name = match token {
+                Token::Text(text) => {
+                    parts.push(Part::Text(text));
+                    continue;
+                }
+                Token::Placeholder(name) => name,
+            };
+            let (before, field, after) = match name.split('|').collect::<Vec<_>>()[..] {
+                [field] => ("", field, ""),
+                [before, field, after] => (before, field, after),
+                _ => bail!(invalid(format!(
+                    "'{{{}}}' isn't '{{name}}' or '{{before|name|after}}'",
+                    name
+                ))),
+            };
+            let field =
+                Field::from_name(field).ok_or_else(|| unknown_placeholder(template, field))?;
+            parts.push(Part::Field {
+                field,
+                before: before.into(),
+                after: after.into(),
+            });
+        }
+        Ok(Template { parts })
+    }
+}
+
+/// A piece of a template, as split up by [`tokenize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Token {
+    /// Text to keep as is, with `{{` and `}}` already made `{` and `}`.
+    Text(String),
+    /// What's between the braces of a placeholder.
+    Placeholder(String),
+}
+
+/// Splits `template` into text and placeholders, failing with
+/// `InvalidTemplate` on unmatched braces.
+pub(crate) fn tokenize(template: &str) -> Result<Vec<Token>> {
+    let invalid = |reason: &str| ErrorKind::InvalidTemplate(template.into(), reason.into());
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => bail!(invalid("unclosed '{'")),
+                    }
+                }
+                if !text.is_empty() {
+                    tokens.push(Token::Text(text.split_off(0)));
+                }
+                tokens.push(Token::Placeholder(name));
+            }
+            '}' => bail!(invalid("unmatched '}', write '}}' for a '}'")),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+/// The error of a placeholder called `name` in `template` not being known.
+pub(crate) fn unknown_placeholder(template: &str, name: &str) -> Error {
+    ErrorKind::InvalidTemplate(
+        template.into(),
+        format!("unknown placeholder '{{{}}}'", name),
+    )
+    .into()
+}
+
+/// Formats `time` as an RFC 3339 timestamp in UTC, to the second.
+pub(crate) fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    // Howard Hinnant's `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
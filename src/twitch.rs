@@ -0,0 +1,487 @@
+#[cfg(feature = "twitch-api")]
+use std::fmt;
+#[cfg(feature = "twitch-api")]
+use std::sync::Mutex;
+#[cfg(feature = "twitch-api")]
+use std::thread;
+#[cfg(feature = "twitch-api")]
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use errors::*;
+#[cfg(feature = "http")]
+use http;
+#[cfg(feature = "http")]
+use StreamStatus;
+
+/// Public client ID of the Twitch website, accepted by its GQL endpoint.
+#[cfg(feature = "http")]
+const CLIENT_ID: &str = "kimne78kx3ncx6brgo4mv6wki5h1ko";
+
+#[cfg(feature = "http")]
+const GQL_URL: &str = "https://gql.twitch.tv/gql";
+
+/// Returns the logins of the channels in the Twitch team `team`, asking
+/// the API of the Twitch website through `proxy`, if any, for all the pages
+/// of its members.
+#[cfg(feature = "http")]
+pub fn team_members(team: &str, proxy: Option<&str>) -> Result<Vec<String>> {
+    team_members_at(GQL_URL, team, proxy)
+}
+
+/// Like [`team_members`], but asks the GQL endpoint at `url`.
+#[cfg(feature = "http")]
+pub(crate) fn team_members_at(url: &str, team: &str, proxy: Option<&str>) -> Result<Vec<String>> {
+    let mut members = vec![];
+    let mut cursor: Option<String> = None;
+    loop {
+        let query = json!({
+            "query": "query($name: String!, $after: Cursor) { team(name: $name) { \
+                      members(first: 100, after: $after) { \
+                      edges { cursor node { login } } pageInfo { hasNextPage } } } }",
+            "variables": { "name": team, "after": cursor },
+        });
+        let response: ::serde_json::Value = http::agent(proxy)?
+            .post(url)
+            .set("Client-ID", CLIENT_ID)
+            .send_json(query)
+            .map_err(|e| e.to_string())?
+            .into_json()?;
+        let (page, next) =
+            parse_team_members(&response).ok_or_else(|| ErrorKind::TeamExpansion(team.into()))?;
+        // An empty page ends the list, even if it has a cursor.
+        let done = page.is_empty();
+        members.extend(page);
+        match next {
+            Some(next) if !done => cursor = Some(next),
+            _ => return Ok(members),
+        }
+    }
+}
+
+/// Reads the logins of the members from a page of the GQL response of
+/// [`team_members`], along with the cursor of the next page, if there's one.
+#[cfg(feature = "http")]
+pub fn parse_team_members(response: &::serde_json::Value) -> Option<(Vec<String>, Option<String>)> {
+    let members = &response["data"]["team"]["members"];
+    let edges = members["edges"].as_array()?;
+    let logins = edges
+        .iter()
+        .filter_map(|edge| edge["node"]["login"].as_str())
+        .map(String::from)
+        .collect();
+    let cursor = match members["pageInfo"]["hasNextPage"].as_bool() {
+        Some(true) => edges
+            .last()
+            .and_then(|edge| edge["cursor"].as_str())
+            .map(String::from),
+        _ => None,
+    };
+    Some((logins, cursor))
+}
+
+/// Checks if the channel with `login` is live, asking through `proxy`, if
+/// any.
+///
+/// Failed requests, including rate-limited ones, are errors rather than
+/// offline channels.
+#[cfg(feature = "http")]
+pub fn stream_status(login: &str, proxy: Option<&str>) -> Result<StreamStatus> {
+    let query = json!({
+        "query": "query($login: String!) { user(login: $login) { stream { id } } }",
+        "variables": { "login": login },
+    });
+    let response: ::serde_json::Value = match http::agent(proxy)?
+        .post(GQL_URL)
+        .set("Client-ID", CLIENT_ID)
+        .send_json(query)
+    {
+        Ok(response) => response.into_json()?,
+        Err(::ureq::Error::Status(429, _)) => bail!("Twitch API rate limit exceeded"),
+        Err(e) => bail!("Twitch API request failed: {}", e),
+    };
+    parse_stream_status(&response)
+        .ok_or_else(|| format!("unexpected Twitch API response for '{}'", login).into())
+}
+
+/// Reads the status of a channel from the GQL response of [`stream_status`].
+#[cfg(feature = "http")]
+pub fn parse_stream_status(response: &::serde_json::Value) -> Option<StreamStatus> {
+    if !response["errors"].is_null() {
+        return None;
+    }
+    let data = response["data"].as_object()?;
+    let status = match data.get("user") {
+        // Channels that don't exist can't be live either.
+        Some(&::serde_json::Value::Null) => StreamStatus::Offline,
+        Some(user) if user["stream"].is_null() => StreamStatus::Offline,
+        Some(_) => StreamStatus::Online,
+        None => return None,
+    };
+    Some(status)
+}
+
+#[cfg(feature = "twitch-api")]
+const HELIX_URL: &str = "https://api.twitch.tv/helix";
+
+#[cfg(feature = "twitch-api")]
+const AUTH_URL: &str = "https://id.twitch.tv/oauth2/token";
+
+#[cfg(feature = "twitch-api")]
+const DEVICE_URL: &str = "https://id.twitch.tv/oauth2/device";
+
+/// The grant of tokens authorized on another device, as told by RFC 8628.
+#[cfg(feature = "twitch-api")]
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// The scope of user tokens, enough to read whom the user follows.
+#[cfg(feature = "twitch-api")]
+const FOLLOWS_SCOPE: &str = "user:read:follows";
+
+/// How many channels Helix accepts in a single request.
+#[cfg(feature = "twitch-api")]
+pub const HELIX_BATCH_SIZE: usize = 100;
+
+/// The longest time to wait for a rate limit to reset before giving up.
+#[cfg(feature = "twitch-api")]
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// A client of the Twitch Helix API, authenticated as an app.
+#[cfg(feature = "twitch-api")]
+pub struct Helix {
+    client_id: String,
+    client_secret: String,
+    proxy: Option<String>,
+    /// The app access token, and when it expires.
+    token: Mutex<Option<(String, Instant)>>,
+    pub(crate) api_url: String,
+    pub(crate) auth_url: String,
+    pub(crate) device_url: String,
+}
+
+/// A code the user enters at `verification_uri` to let streamlink-rs act as
+/// them, got with [`Helix::request_device_code`].
+#[cfg(feature = "twitch-api")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceCode {
+    /// What the user enters, e.g. `ABCDEFGH`.
+    pub user_code: String,
+    /// Where the user enters it, e.g. `https://www.twitch.tv/activate`.
+    pub verification_uri: String,
+    device_code: String,
+    /// How long to wait between asking whether the user entered it.
+    interval: Duration,
+    /// When it can't be entered anymore.
+    expires: Instant,
+}
+
+/// A channel a Twitch user follows.
+#[cfg(feature = "twitch-api")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FollowedChannel {
+    /// Its login, e.g. `gogcom`.
+    pub login: String,
+    /// Its display name, e.g. `GOGcom`.
+    pub name: String,
+}
+
+#[cfg(feature = "twitch-api")]
+impl Helix {
+    /// Creates a client of the app with `client_id` and `client_secret`,
+    /// connecting through `proxy`, if any.
+    pub fn new(client_id: String, client_secret: String, proxy: Option<String>) -> Self {
+        Self {
+            client_id,
+            client_secret,
+            proxy,
+            token: Mutex::new(None),
+            api_url: HELIX_URL.into(),
+            auth_url: AUTH_URL.into(),
+            device_url: DEVICE_URL.into(),
+        }
+    }
+
+    /// Returns the logins of the live channels among `logins`, lowercased.
+    ///
+    /// Expired tokens are refreshed, and rate-limited requests retried once
+    /// the limit resets.
+    pub fn live_logins(&self, logins: &[&str]) -> Result<Vec<String>> {
+        if logins.is_empty() {
+            return Ok(vec![]);
+        }
+        let first = HELIX_BATCH_SIZE.to_string();
+        let mut query = vec![("first", first.as_str())];
+        query.extend(logins.iter().map(|&login| ("user_login", login)));
+        let response = self.get_as_app(&format!("{}/streams", self.api_url), &query)?;
+        parse_live_logins(&response).ok_or_else(|| "unexpected Twitch API response".into())
+    }
+
+    /// Gets `url` with `query`, authorized by the app access token.
+    ///
+    /// Expired tokens are refreshed, and rate-limited requests retried once
+    /// the limit resets.
+    fn get_as_app(&self, url: &str, query: &[(&str, &str)]) -> Result<::serde_json::Value> {
+        let mut refreshed = false;
+        let mut waited = false;
+        loop {
+            let token = self.token(refreshed)?;
+            let mut request = http::agent(self.proxy.as_deref())?
+                .get(url)
+                .set("Client-Id", &self.client_id)
+                .set("Authorization", &format!("Bearer {}", token));
+            for &(name, value) in query {
+                request = request.query(name, value);
+            }
+            match request.call() {
+                Ok(response) => return Ok(response.into_json()?),
+                Err(::ureq::Error::Status(401, _)) if !refreshed => refreshed = true,
+                Err(::ureq::Error::Status(429, ref response)) if !waited => {
+                    waited = true;
+                    thread::sleep(retry_after(response));
+                }
+                Err(::ureq::Error::Status(429, _)) => bail!("Twitch API rate limit exceeded"),
+                Err(e) => bail!("Twitch API request failed: {}", e),
+            }
+        }
+    }
+
+    /// Returns the logins of the channels in the Twitch team `team`.
+    pub fn team_members(&self, team: &str) -> Result<Vec<String>> {
+        let response = self
+            .get_as_app(&format!("{}/teams", self.api_url), &[("name", team)])
+            .chain_err(|| ErrorKind::TeamExpansion(team.into()))?;
+        parse_team_users(&response).ok_or_else(|| ErrorKind::TeamExpansion(team.into()).into())
+    }
+
+    /// Starts authorizing streamlink-rs to read whom a user follows, as told
+    /// by the device flow of OAuth. The user enters the code returned, and
+    /// [`user_token`](#method.user_token) waits for that.
+    pub fn request_device_code(&self) -> Result<DeviceCode> {
+        let response: ::serde_json::Value = http::agent(self.proxy.as_deref())?
+            .post(&self.device_url)
+            .send_form(&[("client_id", &self.client_id), ("scopes", FOLLOWS_SCOPE)])
+            .map_err(|e| format!("Twitch authorization failed: {}", e))?
+            .into_json()?;
+        let field = |name: &str| {
+            response[name]
+                .as_str()
+                .map(String::from)
+                .ok_or_else(|| Error::from(format!("Twitch authorization failed: no {}", name)))
+        };
+        Ok(DeviceCode {
+            user_code: field("user_code")?,
+            verification_uri: field("verification_uri")?,
+            device_code: field("device_code")?,
+            interval: Duration::from_secs(response["interval"].as_u64().unwrap_or(5)),
+            expires: Instant::now()
+                + Duration::from_secs(response["expires_in"].as_u64().unwrap_or(0)),
+        })
+    }
+
+    /// Waits for the user to enter `code`, and returns the user access token
+    /// they authorized.
+    pub fn user_token(&self, code: &DeviceCode) -> Result<String> {
+        let mut interval = code.interval;
+        loop {
+            let response = http::agent(self.proxy.as_deref())?
+                .post(&self.auth_url)
+                .send_form(&[
+                    ("client_id", &self.client_id),
+                    ("scopes", FOLLOWS_SCOPE),
+                    ("device_code", &code.device_code),
+                    ("grant_type", DEVICE_GRANT),
+                ]);
+            let error: ::serde_json::Value = match response {
+                Ok(response) => {
+                    let response: ::serde_json::Value = response.into_json()?;
+                    return response["access_token"]
+                        .as_str()
+                        .map(String::from)
+                        .ok_or_else(|| "Twitch authorization failed: no access token".into());
+                }
+                Err(::ureq::Error::Status(400, response)) => response.into_json()?,
+                Err(e) => bail!("Twitch authorization failed: {}", e),
+            };
+            match error["message"].as_str() {
+                Some("authorization_pending") if Instant::now() < code.expires => {}
+                Some("slow_down") => interval += Duration::from_secs(5),
+                Some("authorization_pending") => {
+                    bail!("Twitch authorization failed: the code expired")
+                }
+                Some(message) => bail!("Twitch authorization failed: {}", message),
+                None => bail!("Twitch authorization failed"),
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    /// Returns the ID and login of the user who authorized `token`.
+    pub fn token_user(&self, token: &str) -> Result<(String, String)> {
+        let response = self.get_as_user(&format!("{}/users", self.api_url), token, &[])?;
+        let user = &response["data"][0];
+        match (user["id"].as_str(), user["login"].as_str()) {
+            (Some(id), Some(login)) => Ok((id.into(), login.into())),
+            _ => bail!("unexpected Twitch API response"),
+        }
+    }
+
+    /// Returns the channels the user with `user_id` follows, following all
+    /// the pages of the list. `token` is theirs, from
+    /// [`user_token`](#method.user_token).
+    pub fn followed_channels(&self, user_id: &str, token: &str) -> Result<Vec<FollowedChannel>> {
+        let url = format!("{}/channels/followed", self.api_url);
+        let first = HELIX_BATCH_SIZE.to_string();
+        let mut channels = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut query = vec![("user_id", user_id), ("first", &first)];
+            if let Some(ref cursor) = cursor {
+                query.push(("after", cursor));
+            }
+            let response = self.get_as_user(&url, token, &query)?;
+            let (page, next) =
+                parse_followed_channels(&response).ok_or("unexpected Twitch API response")?;
+            // An empty page ends the list, even if it has a cursor.
+            let done = page.is_empty();
+            channels.extend(page);
+            match next {
+                Some(next) if !done => cursor = Some(next),
+                _ => return Ok(channels),
+            }
+        }
+    }
+
+    /// Gets `url` with `query`, authorized by the user token `token`.
+    fn get_as_user(
+        &self,
+        url: &str,
+        token: &str,
+        query: &[(&str, &str)],
+    ) -> Result<::serde_json::Value> {
+        let mut request = http::agent(self.proxy.as_deref())?
+            .get(url)
+            .set("Client-Id", &self.client_id)
+            .set("Authorization", &format!("Bearer {}", token));
+        for &(name, value) in query {
+            request = request.query(name, value);
+        }
+        match request.call() {
+            Ok(response) => Ok(response.into_json()?),
+            Err(::ureq::Error::Status(429, _)) => bail!("Twitch API rate limit exceeded"),
+            Err(e) => bail!("Twitch API request failed: {}", e),
+        }
+    }
+
+    /// Returns the app access token, fetching a new one if there is none,
+    /// it has expired, or `refresh` is set.
+    fn token(&self, refresh: bool) -> Result<String> {
+        let mut token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((ref token, expires)) = *token {
+            if !refresh && Instant::now() < expires {
+                return Ok(token.clone());
+            }
+        }
+        let response: ::serde_json::Value = http::agent(self.proxy.as_deref())?
+            .post(&self.auth_url)
+            .send_form(&[
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("grant_type", "client_credentials"),
+            ])
+            .map_err(|e| format!("Twitch authentication failed: {}", e))?
+            .into_json()?;
+        let access_token = response["access_token"]
+            .as_str()
+            .ok_or("Twitch authentication failed: no access token")?
+            .to_string();
+        let expires_in = response["expires_in"].as_u64().unwrap_or(0);
+        *token = Some((
+            access_token.clone(),
+            Instant::now() + Duration::from_secs(expires_in),
+        ));
+        Ok(access_token)
+    }
+}
+
+#[cfg(feature = "twitch-api")]
+impl fmt::Debug for Helix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Helix")
+            .field("client_id", &self.client_id)
+            .field("proxy", &self.proxy)
+            .field("api_url", &self.api_url)
+            .finish()
+    }
+}
+
+/// Returns how long to wait before retrying a rate-limited request.
+#[cfg(feature = "twitch-api")]
+fn retry_after(response: &::ureq::Response) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let wait = match response.header("Ratelimit-Reset") {
+        Some(reset) => reset.parse::<u64>().map(|reset| reset.saturating_sub(now)),
+        None => response.header("Retry-After").unwrap_or("1").parse(),
+    };
+    Duration::from_secs(wait.unwrap_or(1)).min(MAX_RETRY_AFTER)
+}
+
+/// Reads the logins of the live channels from a Helix `streams` response.
+#[cfg(feature = "twitch-api")]
+pub fn parse_live_logins(response: &::serde_json::Value) -> Option<Vec<String>> {
+    let streams = response["data"].as_array()?;
+    Some(
+        streams
+            .iter()
+            .filter(|stream| stream["type"] == "live")
+            .filter_map(|stream| stream["user_login"].as_str())
+            .map(str::to_lowercase)
+            .collect(),
+    )
+}
+
+/// Reads the logins of the members from a Helix `teams` response.
+#[cfg(feature = "twitch-api")]
+pub fn parse_team_users(response: &::serde_json::Value) -> Option<Vec<String>> {
+    let users = response["data"][0]["users"].as_array()?;
+    Some(
+        users
+            .iter()
+            .filter_map(|user| user["user_login"].as_str())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Reads the channels from a Helix `channels/followed` response, along with
+/// the cursor of the next page, if there's one.
+#[cfg(feature = "twitch-api")]
+pub fn parse_followed_channels(
+    response: &::serde_json::Value,
+) -> Option<(Vec<FollowedChannel>, Option<String>)> {
+    let channels = response["data"]
+        .as_array()?
+        .iter()
+        .map(|channel| {
+            let login = channel["broadcaster_login"].as_str()?;
+            Some(FollowedChannel {
+                login: login.into(),
+                name: channel["broadcaster_name"].as_str().unwrap_or(login).into(),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let cursor = response["pagination"]["cursor"]
+        .as_str()
+        .filter(|cursor| !cursor.is_empty())
+        .map(String::from);
+    Some((channels, cursor))
+}
+
+/// Returns the logins of the channels in the Twitch team `team`.
+///
+/// Always fails, as fetching the team requires the `http` feature.
+#[cfg(not(feature = "http"))]
+pub fn team_members(team: &str, _proxy: Option<&str>) -> Result<Vec<String>> {
+    bail!(ErrorKind::TeamExpansion(team.into()))
+}
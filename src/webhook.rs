@@ -0,0 +1,112 @@
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use serde_json;
+use ureq::Agent;
+
+use errors::*;
+use http;
+use {StatusReport, StreamStatus, Template, WebhookConfig};
+
+/// How many times a failed post is retried.
+const RETRIES: u32 = 2;
+
+/// Posts streams going online or offline to a webhook, as set in
+/// [`Config::webhook`](struct.Config.html#structfield.webhook).
+pub(crate) struct Webhook {
+    url: String,
+    body: Option<Template>,
+    on: Vec<StreamStatus>,
+    agent: Agent,
+    retry_delay: Duration,
+}
+
+impl Webhook {
+    /// The webhook set in `config`, posting through `proxy`, if any, and
+    /// waiting `retry_delay` before the first retry of a failed post. Fails
+    /// on a broken body template.
+    pub(crate) fn new(
+        config: &WebhookConfig,
+        proxy: Option<&str>,
+        retry_delay: Duration,
+    ) -> Result<Self> {
+        let body = match config.body {
+            Some(ref body) => Some(body.parse::<Template>()?),
+            None => None,
+        };
+        Ok(Webhook {
+            url: config.url.clone(),
+            body,
+            on: config.on.clone(),
+            agent: http::agent(proxy)?,
+            retry_delay,
+        })
+    }
+
+    /// Returns what to post about `report`: the body template filled in, or
+    /// else the report as JSON.
+    fn body(&self, report: &StatusReport) -> Result<String> {
+        match self.body {
+            Some(ref template) => {
+                let escaped = StatusReport {
+                    name: escape_json(&report.name),
+                    url: escape_json(&report.url),
+                    platform: escape_json(&report.platform),
+                    title: report.title.as_deref().map(escape_json),
+                    ..report.clone()
+                };
+                Ok(template.render(&escaped, false))
+            }
+            None => serde_json::to_string(report).chain_err(|| "unable to serialize status"),
+        }
+    }
+
+    /// Posts `report`, retrying [`RETRIES`] times, each retry waiting twice
+    /// as long as the one before.
+    fn post(&self, report: &StatusReport) -> Result<()> {
+        let body = self.body(report)?;
+        let mut delay = self.retry_delay;
+        let mut retries = 0;
+        loop {
+            let response = self
+                .agent
+                .post(&self.url)
+                .set("Content-Type", "application/json")
+                .send_string(&body);
+            match response {
+                Ok(_) => return Ok(()),
+                Err(_) if retries < RETRIES => {
+                    retries += 1;
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(e) => bail!(ErrorKind::WebhookFailed(e.to_string())),
+            }
+        }
+    }
+}
+
+/// Posts the streams in `reports` that went to a status `webhook` is on for.
+/// Posts that fail even when retried are told to `err`, and don't keep the
+/// others from being posted.
+pub(crate) fn post_changes<E: Write>(webhook: &Webhook, reports: &[StatusReport], err: &mut E) {
+    let changed = reports
+        .iter()
+        .filter(|report| report.changed && webhook.on.contains(&report.status));
+    for report in changed {
+        if let Err(e) = webhook.post(report) {
+            let _ = writeln!(
+                err,
+                "warning: unable to post {} to the webhook: {}",
+                report.name, e
+            );
+        }
+    }
+}
+
+/// Escapes `text` to fit within the quotes of a JSON string.
+fn escape_json(text: &str) -> String {
+    let quoted = serde_json::Value::from(text).to_string();
+    quoted[1..quoted.len() - 1].into()
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use url::Url;
+
+use errors::*;
+use http;
+use platform::host_matches;
+use {Stream, StreamStatus};
+
+const DATA_API_URL: &str = "https://www.googleapis.com/youtube/v3";
+
+/// What a YouTube stream URL points at.
+#[derive(Debug, PartialEq)]
+enum Target<'a> {
+    Channel(&'a str),
+    Username(&'a str),
+    Handle(&'a str),
+    CustomUrl(&'a str),
+    Video(&'a str),
+}
+
+impl<'a> Target<'a> {
+    fn from_stream(stream: &'a Stream) -> Option<Self> {
+        let name = stream.name()?;
+        let url: &Url = stream.url();
+        if let Some(host) = url.host_str() {
+            if host_matches(host, "youtu.be") {
+                return Some(Target::Video(name));
+            }
+        }
+        let first = url
+            .path_segments()
+            .and_then(|mut segments| segments.find(|s| !s.is_empty()))
+            .unwrap_or("");
+        let target = match first {
+            "channel" => Target::Channel(name),
+            "user" => Target::Username(name),
+            "watch" => Target::Video(name),
+            handle if handle.starts_with('@') => Target::Handle(name),
+            _ => Target::CustomUrl(name),
+        };
+        Some(target)
+    }
+}
+
+/// A client of the YouTube Data API, authenticated with an API key.
+pub struct DataApi {
+    api_key: String,
+    /// IDs of the channels resolved from user, handle, and custom URLs.
+    channel_ids: Mutex<HashMap<String, Option<String>>>,
+    proxy: Option<String>,
+    pub(crate) api_url: String,
+}
+
+impl DataApi {
+    /// Creates a client calling the API with `api_key`, connecting through
+    /// `proxy`, if any.
+    pub fn new(api_key: String, proxy: Option<String>) -> Self {
+        Self {
+            api_key,
+            proxy,
+            channel_ids: Mutex::new(HashMap::new()),
+            api_url: DATA_API_URL.into(),
+        }
+    }
+
+    /// Checks if the YouTube channel or video of `stream` is live.
+    ///
+    /// Channels and videos that don't exist are offline. Failed requests,
+    /// including ones over the quota, are errors.
+    pub fn stream_status(&self, stream: &Stream) -> Result<StreamStatus> {
+        let target = Target::from_stream(stream)
+            .ok_or_else(|| ErrorKind::MissingStreamName(stream.url().to_string()))?;
+        let channel_id = match target {
+            Target::Video(id) => return self.video_status(id),
+            Target::Channel(id) => Some(id.to_string()),
+            _ => self.channel_id(&target)?,
+        };
+        match channel_id {
+            Some(channel_id) => self.channel_status(&channel_id),
+            None => Ok(StreamStatus::Offline),
+        }
+    }
+
+    fn video_status(&self, id: &str) -> Result<StreamStatus> {
+        let response = self.get("videos", &[("part", "snippet"), ("id", id)])?;
+        let live = response["items"][0]["snippet"]["liveBroadcastContent"] == "live";
+        Ok(if live {
+            StreamStatus::Online
+        } else {
+            StreamStatus::Offline
+        })
+    }
+
+    fn channel_status(&self, channel_id: &str) -> Result<StreamStatus> {
+        let response = self.get(
+            "search",
+            &[
+                ("part", "id"),
+                ("channelId", channel_id),
+                ("eventType", "live"),
+                ("type", "video"),
+                ("maxResults", "1"),
+            ],
+        )?;
+        let items = response["items"]
+            .as_array()
+            .ok_or("unexpected YouTube API response")?;
+        Ok(if items.is_empty() {
+            StreamStatus::Offline
+        } else {
+            StreamStatus::Online
+        })
+    }
+
+    /// Resolves the ID of the channel `target` points at, caching it.
+    fn channel_id(&self, target: &Target) -> Result<Option<String>> {
+        let key = format!("{:?}", target).to_lowercase();
+        if let Some(id) = self.lock_channel_ids().get(&key) {
+            return Ok(id.clone());
+        }
+        let id = match *target {
+            Target::Username(name) => self
+                .get("channels", &[("part", "id"), ("forUsername", name)])?["items"][0]["id"]
+                .as_str()
+                .map(String::from),
+            Target::Handle(name) => self.get(
+                "channels",
+                &[("part", "id"), ("forHandle", &format!("@{}", name))],
+            )?["items"][0]["id"]
+                .as_str()
+                .map(String::from),
+            Target::CustomUrl(name) => self.get(
+                "search",
+                &[
+                    ("part", "id"),
+                    ("type", "channel"),
+                    ("q", name),
+                    ("maxResults", "1"),
+                ],
+            )?["items"][0]["id"]["channelId"]
+                .as_str()
+                .map(String::from),
+            Target::Channel(id) => Some(id.into()),
+            Target::Video(_) => None,
+        };
+        self.lock_channel_ids().insert(key, id.clone());
+        Ok(id)
+    }
+
+    fn lock_channel_ids(&self) -> MutexGuard<'_, HashMap<String, Option<String>>> {
+        self.channel_ids.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Calls the `endpoint` of the API with `params`.
+    fn get(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<::serde_json::Value> {
+        let mut request =
+            http::agent(self.proxy.as_deref())?.get(&format!("{}/{}", self.api_url, endpoint));
+        for &(key, value) in params {
+            request = request.query(key, value);
+        }
+        match request.query("key", &self.api_key).call() {
+            Ok(response) => Ok(response.into_json()?),
+            Err(::ureq::Error::Status(_, response)) => {
+                let error: ::serde_json::Value = response.into_json().unwrap_or_default();
+                match error["error"]["errors"][0]["reason"].as_str() {
+                    Some("quotaExceeded")
+                    | Some("dailyLimitExceeded")
+                    | Some("rateLimitExceeded") => bail!("YouTube API quota exceeded"),
+                    _ => bail!(
+                        "YouTube API request failed: {}",
+                        error["error"]["message"]
+                            .as_str()
+                            .unwrap_or("unknown error")
+                    ),
+                }
+            }
+            Err(e) => bail!("YouTube API request failed: {}", e),
+        }
+    }
+}
+
+impl fmt::Debug for DataApi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DataApi")
+            .field("proxy", &self.proxy)
+            .field("api_url", &self.api_url)
+            .finish()
+    }
+}
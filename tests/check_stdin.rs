@@ -0,0 +1,82 @@
+// The backend in these tests is a shell script.
+#![cfg(unix)]
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Writes a fake `yt-dlp` to a directory of its own, named after `name`,
+/// that tells Twitch streams online and the rest offline, and returns that
+/// directory.
+fn fake_backend(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("yt-dlp");
+    let script = "#!/bin/sh\ncase \"$2\" in\n*twitch.tv/*) exit 0 ;;\n*) exit 1 ;;\nesac\n";
+    fs::write(&path, script).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    dir
+}
+
+/// Runs `strs check` with `args`, with only `path` to find the backend in,
+/// and `stdin` piped to it.
+fn check(path: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_streamlink-rs"))
+        .args(["--color", "never", "check"])
+        .args(args)
+        .env("PATH", path)
+        .env_remove("HTTPS_PROXY")
+        .env_remove("https_proxy")
+        .env_remove("HTTP_PROXY")
+        .env_remove("http_proxy")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run strs");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn urls_from_stdin() {
+    let path = fake_backend("check-stdin");
+    let stdin = "# streams to check\n\
+                 https://twitch.tv/gogcom\n\
+                 \n\
+                 https://kick.com/xqc\n";
+    let output = check(&path, &["-"], stdin);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(Some(1), output.status.code(), "{}", stdout);
+    assert!(stdout.contains("gogcom is online"), "{}", stdout);
+    assert!(stdout.contains("xqc is offline"), "{}", stdout);
+
+    // Along with the URLs on the command line.
+    let output = check(
+        &path,
+        &["https://kick.com/xqc", "-"],
+        "https://twitch.tv/gogcom",
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("gogcom is online"), "{}", stdout);
+    assert!(stdout.contains("xqc is offline"), "{}", stdout);
+}
+
+#[test]
+fn invalid_urls_from_stdin() {
+    let path = fake_backend("check-stdin-invalid");
+    let stdin = "https://twitch.tv/gogcom\nnot-a-url https://example.com/video\n";
+    let output = check(&path, &["-"], stdin);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // The other streams are still checked.
+    assert_eq!(Some(2), output.status.code(), "{}", stdout);
+    assert_eq!(2, stdout.matches("error: line 2: ").count(), "{}", stdout);
+    assert!(stdout.contains("gogcom is online"), "{}", stdout);
+}